@@ -0,0 +1,48 @@
+//! Compare deserializing [`CapVec`] with a large cap against a plain [`Vec`].
+//!
+//! Run with `cargo bench --features serde` to see the number of allocations and bytes allocated by each.
+
+use capped::CapVec;
+use divan::{AllocProfiler, Bencher};
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+const LENS: &[usize] = &[0, 1, 16, 256];
+
+fn json(len: usize) -> String {
+    serde_json::to_string(&vec![7u32; len]).expect("serialize input")
+}
+
+fn main() {
+    divan::main();
+}
+
+/// JSON gives no size hint, so the elements are pushed as they arrive
+#[divan::bench(args = LENS)]
+fn json_cap_vec(bencher: Bencher, len: usize) {
+    let input = json(len);
+    bencher.bench(|| serde_json::from_str::<CapVec<100_000, u32>>(&input).expect("input fits"));
+}
+
+#[divan::bench(args = LENS)]
+fn json_vec(bencher: Bencher, len: usize) {
+    let input = json(len);
+    bencher.bench(|| serde_json::from_str::<Vec<u32>>(&input).expect("input fits"));
+}
+
+/// `MessagePack` gives the length up front, so the space is reserved once
+#[divan::bench(args = LENS)]
+fn msgpack_cap_vec(bencher: Bencher, len: usize) {
+    let input = rmp_serde::to_vec(&vec![7u32; len]).expect("serialize input");
+    bencher.bench(|| rmp_serde::from_slice::<CapVec<100_000, u32>>(&input).expect("input fits"));
+}
+
+/// Oversize input with a size hint is rejected without reading any elements
+#[divan::bench]
+fn msgpack_reject(bencher: Bencher) {
+    let input = rmp_serde::to_vec(&vec![7u32; 10_000]).expect("serialize input");
+    bencher.bench(|| {
+        rmp_serde::from_slice::<CapVec<100, u32>>(&input).expect_err("input is too long")
+    });
+}
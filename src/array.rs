@@ -0,0 +1,134 @@
+use crate::CapUsize;
+
+/// A wrapper around an array `[T; N]` that can be indexed by [`CapUsize<N>`] without a bounds check.
+///
+/// A [`CapUsize<N>`] is always in the range `0..N` so it is always a valid index.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CapArray<const N: usize, T>([T; N]);
+
+impl<const N: usize, T> CapArray<N, T> {
+    /// Create a new [`CapArray`] from an array
+    #[must_use]
+    pub const fn new(array: [T; N]) -> Self {
+        Self(array)
+    }
+
+    /// Get the underlying array
+    #[must_use]
+    pub fn into_inner(self) -> [T; N] {
+        self.0
+    }
+
+    /// Returns a reference to the element at `index`.
+    #[must_use]
+    pub fn get_capped(&self, index: CapUsize<N>) -> &T {
+        #[allow(unsafe_code)]
+        // Safety: `CapUsize<N>` is always less than `N` which is the length of the array
+        unsafe {
+            self.0.get_unchecked(index.into_inner())
+        }
+    }
+
+    /// Returns a mutable reference to the element at `index`.
+    #[must_use]
+    pub fn get_capped_mut(&mut self, index: CapUsize<N>) -> &mut T {
+        #[allow(unsafe_code)]
+        // Safety: `CapUsize<N>` is always less than `N` which is the length of the array
+        unsafe {
+            self.0.get_unchecked_mut(index.into_inner())
+        }
+    }
+
+    /// Iterate over the elements paired with their index as a [`CapUsize<N>`].
+    ///
+    /// See [`Iterator::enumerate`]
+    #[must_use]
+    pub fn enumerate_capped(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (CapUsize<N>, &T)> + DoubleEndedIterator {
+        CapUsize::<N>::all().zip(self.0.iter())
+    }
+}
+
+impl<const N: usize, T> From<[T; N]> for CapArray<N, T> {
+    fn from(value: [T; N]) -> Self {
+        Self(value)
+    }
+}
+
+impl<const N: usize, T> From<CapArray<N, T>> for [T; N] {
+    fn from(value: CapArray<N, T>) -> Self {
+        value.0
+    }
+}
+
+impl<const N: usize, T> AsRef<[T; N]> for CapArray<N, T> {
+    fn as_ref(&self) -> &[T; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize, T> core::ops::Deref for CapArray<N, T> {
+    type Target = [T; N];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize, T> core::ops::DerefMut for CapArray<N, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const N: usize, T> core::ops::Index<CapUsize<N>> for CapArray<N, T> {
+    type Output = T;
+
+    fn index(&self, index: CapUsize<N>) -> &Self::Output {
+        self.get_capped(index)
+    }
+}
+
+impl<const N: usize, T> core::ops::IndexMut<CapUsize<N>> for CapArray<N, T> {
+    fn index_mut(&mut self, index: CapUsize<N>) -> &mut Self::Output {
+        self.get_capped_mut(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CapArray;
+    use crate::CapUsize;
+
+    #[test]
+    fn index() {
+        let mut arr = CapArray::new([10, 20, 30]);
+        let i = CapUsize::<3>::new_wrap(1);
+        assert_eq!(arr[i], 20);
+        arr[i] = 25;
+        assert_eq!(*arr.get_capped(i), 25);
+        *arr.get_capped_mut(CapUsize::new_wrap(2)) += 1;
+        assert_eq!(arr.into_inner(), [10, 25, 31]);
+        assert_eq!(arr.len(), 3);
+    }
+
+    #[test]
+    fn enumerate() {
+        let arr = CapArray::from(['a', 'b', 'c']);
+        let items: Vec<_> = arr.enumerate_capped().collect();
+        assert_eq!(
+            items,
+            [
+                (CapUsize::new_wrap(0), &'a'),
+                (CapUsize::new_wrap(1), &'b'),
+                (CapUsize::new_wrap(2), &'c')
+            ]
+        );
+        assert_eq!(
+            arr.enumerate_capped().next_back(),
+            Some((CapUsize::new_wrap(2), &'c'))
+        );
+        assert_eq!(<[char; 3]>::from(arr), ['a', 'b', 'c']);
+    }
+}
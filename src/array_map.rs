@@ -0,0 +1,460 @@
+//! [`CapArrayMap`], a map stored inline with one slot per value of a capped integer key
+//!
+//! The map serializes as a map by default, the `dense` module serializes it as an array instead.
+
+use core::marker::PhantomData;
+
+use crate::CapNum;
+
+/// A map from a capped integer `K` to `V` stored inline as an array with one slot per key.
+///
+/// Stable Rust can't size an array by `K::COUNT` so the number of slots is given as `N`, which must be equal to [`CapNum::COUNT`] for `K`.
+/// This is checked at compile time.
+///
+/// ```
+/// use capped::{CapArrayMap, CapU8};
+///
+/// let mut per_hour = CapArrayMap::<CapU8<24>, u32, 24>::new();
+/// per_hour.insert(CapU8::new_wrap(13), 5);
+/// assert_eq!(per_hour.get(CapU8::new_wrap(13)), Some(&5));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CapArrayMap<K, V, const N: usize> {
+    slots: [Option<V>; N],
+    marker: PhantomData<K>,
+}
+
+impl<K, V, const N: usize> CapArrayMap<K, V, N>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    const VALID: () = assert!(N == K::COUNT, "N must be equal to K::COUNT");
+
+    /// Create an empty [`CapArrayMap`]
+    #[must_use]
+    pub fn new() -> Self {
+        let () = Self::VALID;
+        Self {
+            slots: [(); N].map(|()| None),
+            marker: PhantomData,
+        }
+    }
+
+    fn slot(&self, key: K) -> Option<&V> {
+        self.slots[Self::index(key)].as_ref()
+    }
+
+    fn slot_mut(&mut self, key: K) -> &mut Option<V> {
+        &mut self.slots[Self::index(key)]
+    }
+
+    fn index(key: K) -> usize {
+        key.into_inner()
+            .try_into()
+            .ok()
+            .expect("key is less than K::COUNT which fits in usize")
+    }
+
+    fn key(index: usize) -> K {
+        K::Inner::try_from(index)
+            .ok()
+            .and_then(|inner| K::try_from_inner(inner).ok())
+            .expect("index is less than N which is equal to K::COUNT")
+    }
+
+    /// Inserts a value returning the previous value for the key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.slot_mut(key).replace(value)
+    }
+
+    /// Removes the value for the key returning it
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        self.slot_mut(key).take()
+    }
+
+    /// Returns a reference to the value for the key
+    #[must_use]
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.slot(key)
+    }
+
+    /// Returns a mutable reference to the value for the key
+    #[must_use]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        self.slot_mut(key).as_mut()
+    }
+
+    /// Returns [`true`] if the map contains a value for the key
+    #[must_use]
+    pub fn contains_key(&self, key: K) -> bool {
+        self.slot(key).is_some()
+    }
+
+    /// Gets the entry for the key for in place manipulation
+    pub fn entry(&mut self, key: K) -> CapArrayMapEntry<'_, K, V> {
+        CapArrayMapEntry {
+            key,
+            slot: self.slot_mut(key),
+        }
+    }
+
+    /// Returns the number of values in the map
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|s| s.is_some()).count()
+    }
+
+    /// Returns [`true`] if the map contains no values
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    /// Removes every value from the map
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|s| *s = None);
+    }
+
+    /// Iterate over the keys and values in key order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (K, &V)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|v| (Self::key(i), v)))
+    }
+
+    /// Iterate over the keys and mutable values in key order
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (K, &mut V)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, s)| s.as_mut().map(|v| (Self::key(i), v)))
+    }
+
+    /// Iterate over the keys in order
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = K> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over the values in key order
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> {
+        self.slots.iter().filter_map(Option::as_ref)
+    }
+}
+
+impl<K, V, const N: usize> Default for CapArrayMap<K, V, N>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, const N: usize> core::fmt::Debug for CapArrayMap<K, V, N>
+where
+    K: CapNum + core::fmt::Debug,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+    V: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, const N: usize> FromIterator<(K, V)> for CapArrayMap<K, V, N>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+/// A view into a single slot of a [`CapArrayMap`], which may be empty.
+///
+/// Created by [`CapArrayMap::entry`]
+#[derive(Debug)]
+pub struct CapArrayMapEntry<'a, K, V> {
+    key: K,
+    slot: &'a mut Option<V>,
+}
+
+impl<'a, K: Copy, V> CapArrayMapEntry<'a, K, V> {
+    /// The key of this entry
+    #[must_use]
+    pub const fn key(&self) -> K {
+        self.key
+    }
+
+    /// Inserts `default` if the entry is empty and returns a mutable reference to the value
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.slot.get_or_insert(default)
+    }
+
+    /// Inserts the result of `default` if the entry is empty and returns a mutable reference to the value
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.slot.get_or_insert_with(default)
+    }
+
+    /// Inserts the default value if the entry is empty and returns a mutable reference to the value
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.slot.get_or_insert_with(V::default)
+    }
+
+    /// Calls `f` with the value if the entry is occupied
+    #[must_use]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        if let Some(v) = self.slot.as_mut() {
+            f(v);
+        }
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V, const N: usize> serde::Serialize for CapArrayMap<K, V, N>
+where
+    K: CapNum + serde::Serialize,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+    V: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V, const N: usize> serde::Deserialize<'de> for CapArrayMap<K, V, N>
+where
+    K: CapNum + serde::Deserialize<'de>,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::{Error as _, Visitor};
+
+        struct CapArrayMapVisitor<K, V, const N: usize>(PhantomData<(K, V)>);
+
+        impl<'de, K, V, const N: usize> Visitor<'de> for CapArrayMapVisitor<K, V, N>
+        where
+            K: CapNum + serde::Deserialize<'de>,
+            K::Inner: TryFrom<usize> + TryInto<usize>,
+            V: serde::Deserialize<'de>,
+        {
+            type Value = CapArrayMap<K, V, N>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_fmt(format_args!("a map with keys in the range 0..{N}"))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut values = CapArrayMap::new();
+                while let Some((k, v)) = map.next_entry()? {
+                    if values.insert(k, v).is_some() {
+                        return Err(A::Error::custom("duplicate key"));
+                    }
+                }
+                Ok(values)
+            }
+        }
+        deserializer.deserialize_map(CapArrayMapVisitor(PhantomData))
+    }
+}
+
+/// Serialize a [`CapArrayMap`] as an array with one entry per key for use with `#[serde(with = "capped::array_map::dense")]`
+///
+/// Empty slots are serialized as `None`, so in JSON a `CapArrayMap<CapU8<3>, u32, 3>` looks like `[null, 4, null]`.
+/// This is more compact than the map form when most keys have a value.
+///
+/// ```
+/// use capped::{CapArrayMap, CapU8};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Stats {
+///     #[serde(with = "capped::array_map::dense")]
+///     per_hour: CapArrayMap<CapU8<3>, u32, 3>,
+/// }
+///
+/// let stats: Stats = serde_json::from_str(r#"{"per_hour": [null, 4, 1]}"#).unwrap();
+/// assert_eq!(stats.per_hour.get(CapU8::new_wrap(1)), Some(&4));
+/// assert!(serde_json::from_str::<Stats>(r#"{"per_hour": [null, 4]}"#).is_err());
+/// ```
+#[cfg(feature = "serde")]
+pub mod dense {
+    use core::marker::PhantomData;
+
+    use serde::de::{Deserializer, Error as _, SeqAccess, Visitor};
+    use serde::ser::{SerializeTuple as _, Serializer};
+    use serde::{Deserialize, Serialize};
+
+    use super::CapArrayMap;
+    use crate::CapNum;
+
+    /// Serialize a [`CapArrayMap`] as an array of `N` optional values
+    ///
+    /// # Errors
+    /// Will return `Err` if the serializer fails.
+    pub fn serialize<S, K, V, const N: usize>(
+        map: &CapArrayMap<K, V, N>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize,
+    {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for slot in &map.slots {
+            tuple.serialize_element(slot)?;
+        }
+        tuple.end()
+    }
+
+    /// Deserialize a [`CapArrayMap`] from an array of exactly `N` optional values
+    ///
+    /// # Errors
+    /// Will return `Err` if the array does not have `N` entries or a value is invalid.
+    pub fn deserialize<'de, D, K, V, const N: usize>(
+        deserializer: D,
+    ) -> Result<CapArrayMap<K, V, N>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: CapNum,
+        K::Inner: TryFrom<usize> + TryInto<usize>,
+        V: Deserialize<'de>,
+    {
+        struct DenseVisitor<K, V, const N: usize>(PhantomData<(K, V)>);
+
+        impl<'de, K, V, const N: usize> Visitor<'de> for DenseVisitor<K, V, N>
+        where
+            K: CapNum,
+            K::Inner: TryFrom<usize> + TryInto<usize>,
+            V: Deserialize<'de>,
+        {
+            type Value = CapArrayMap<K, V, N>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_fmt(format_args!("an array of {N} optional values"))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut map = CapArrayMap::new();
+                for (i, slot) in map.slots.iter_mut().enumerate() {
+                    *slot = seq
+                        .next_element()?
+                        .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+                }
+                Ok(map)
+            }
+        }
+        deserializer.deserialize_tuple(N, DenseVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CapArrayMap;
+    use crate::{CapU16, CapU8};
+
+    type PerHour = CapArrayMap<CapU8<24>, u32, 24>;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map = PerHour::new();
+        assert!(map.is_empty());
+        assert_eq!(map.insert(CapU8::new_wrap(5), 10), None);
+        assert_eq!(map.insert(CapU8::new_wrap(5), 11), Some(10));
+        assert_eq!(map.insert(CapU8::new_wrap(23), 1), None);
+        assert_eq!(map.insert(CapU8::new_wrap(0), 7), None);
+        assert_eq!(map.get(CapU8::new_wrap(5)), Some(&11));
+        assert!(map.contains_key(CapU8::new_wrap(23)));
+        assert_eq!(map.len(), 3);
+        *map.get_mut(CapU8::new_wrap(0)).unwrap() += 1;
+        assert_eq!(
+            map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            [(0, 8), (5, 11), (23, 1)]
+        );
+        assert_eq!(map.keys().next_back(), Some(CapU8::new_wrap(23)));
+        assert_eq!(map.values().sum::<u32>(), 20);
+        assert_eq!(map.remove(CapU8::new_wrap(5)), Some(11));
+        assert_eq!(map.remove(CapU8::new_wrap(5)), None);
+        for (_, v) in map.iter_mut() {
+            *v = 0;
+        }
+        assert_eq!(format!("{map:?}"), "{CapU8(0): 0, CapU8(23): 0}");
+        map.clear();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn entry() {
+        let mut map = CapArrayMap::<CapU16<300>, Vec<u8>, 300>::default();
+        let key = CapU16::new_wrap(299);
+        map.entry(key).or_default().push(1);
+        map.entry(key).or_insert_with(Vec::new).push(2);
+        map.entry(key).and_modify(|v| v.push(3)).or_insert(vec![9]);
+        assert_eq!(map.entry(key).key(), key);
+        assert_eq!(map.get(key), Some(&vec![1, 2, 3]));
+        let other = CapU16::new_wrap(1);
+        map.entry(other)
+            .and_modify(|v| v.push(3))
+            .or_insert(vec![9]);
+        assert_eq!(map.get(other), Some(&vec![9]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_array_map() -> serde_json::Result<()> {
+        let map: PerHour = serde_json::from_str(r#"{"3": 4, "20": 1}"#)?;
+        assert_eq!(map.get(CapU8::new_wrap(3)), Some(&4));
+        assert_eq!(serde_json::to_string(&map)?, r#"{"3":4,"20":1}"#);
+        assert!(serde_json::from_str::<PerHour>(r#"{"24": 1}"#).is_err());
+        let err = serde_json::from_str::<PerHour>(r#"{"3": 4, "3": 5}"#).unwrap_err();
+        assert!(err.to_string().starts_with("duplicate key"), "{err}");
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_dense() -> serde_json::Result<()> {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Counts {
+            #[serde(with = "crate::array_map::dense")]
+            per_day: CapArrayMap<CapU8<4>, u32, 4>,
+        }
+
+        let counts: Counts = serde_json::from_str(r#"{"per_day": [1, null, 3, null]}"#)?;
+        assert_eq!(counts.per_day.get(CapU8::new_wrap(2)), Some(&3));
+        assert_eq!(counts.per_day.len(), 2);
+        assert_eq!(
+            serde_json::to_string(&counts)?,
+            r#"{"per_day":[1,null,3,null]}"#
+        );
+        assert!(serde_json::from_str::<Counts>(r#"{"per_day": [1, null, 3]}"#).is_err());
+        assert!(serde_json::from_str::<Counts>(r#"{"per_day": [1, null, 3, 4, 5]}"#).is_err());
+        Ok(())
+    }
+}
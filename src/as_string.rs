@@ -0,0 +1,125 @@
+//! Serialize capped integers as strings for use with `#[serde(with = "capped::as_string")]`
+//!
+//! This is useful for clients such as JavaScript that cannot represent every [`u64`] exactly.
+//! Deserializing human readable formats accepts both strings such as `"42"` and plain integers.
+//! Formats that are not human readable, such as `bincode` and `postcard`, only accept strings since they can't report which type they hold.
+
+use core::marker::PhantomData;
+
+use serde::de::{Deserializer, Error, IntoDeserializer as _, Visitor};
+use serde::{Deserialize, Serializer};
+
+use crate::num::CapNum;
+
+/// Serialize a capped integer as a string
+///
+/// # Errors
+/// Will return `Err` if the serializer fails.
+pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: CapNum + core::fmt::Display,
+{
+    serializer.collect_str(value)
+}
+
+/// Deserialize a capped integer from a string or, in human readable formats, an integer
+///
+/// # Errors
+/// Will return `Err` if the input is not a number or is out of range.
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: CapNum + core::str::FromStr + Deserialize<'de>,
+    T::Err: core::fmt::Display,
+{
+    struct StringVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> Visitor<'de> for StringVisitor<T>
+    where
+        T: CapNum + core::str::FromStr + Deserialize<'de>,
+        T::Err: core::fmt::Display,
+    {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            let end = T::COUNT;
+            formatter.write_fmt(format_args!("an integer or string in the range 0..{end}"))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            v.parse().map_err(E::custom)
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            T::deserialize(v.into_deserializer())
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            T::deserialize(v.into_deserializer())
+        }
+    }
+
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(StringVisitor(PhantomData))
+    } else {
+        deserializer.deserialize_str(StringVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CapU64, CapU8};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Message {
+        #[serde(with = "super")]
+        id: CapU64<{ u64::MAX }>,
+        #[serde(with = "super")]
+        level: CapU8<5>,
+    }
+
+    #[test]
+    fn as_string() -> serde_json::Result<()> {
+        let m = Message {
+            id: CapU64::new_wrap(u64::MAX - 1),
+            level: CapU8::new_wrap(3),
+        };
+        let json = serde_json::to_string(&m)?;
+        assert_eq!(json, r#"{"id":"18446744073709551614","level":"3"}"#);
+        assert_eq!(serde_json::from_str::<Message>(&json)?, m);
+        assert_eq!(
+            serde_json::from_str::<Message>(r#"{"id":18446744073709551614,"level":3}"#)?,
+            m
+        );
+
+        let err = serde_json::from_str::<Message>(r#"{"id":"1","level":"5"}"#).unwrap_err();
+        assert!(err.to_string().contains("value 5 is not in range 0..5"));
+        let err = serde_json::from_str::<Message>(r#"{"id":"1","level":"x"}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid number"));
+        let err = serde_json::from_str::<Message>(r#"{"id":"1","level":-1}"#).unwrap_err();
+        assert!(err.to_string().contains("number -1 is not in range 0..5"));
+        Ok(())
+    }
+
+    #[test]
+    fn not_human_readable() {
+        let m = Message {
+            id: CapU64::new_wrap(7),
+            level: CapU8::new_wrap(4),
+        };
+        let bytes = rmp_serde::to_vec(&m).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Message>(&bytes).unwrap(), m);
+        let bytes = rmp_serde::to_vec(&("7", "5")).unwrap();
+        assert!(rmp_serde::from_slice::<Message>(&bytes).is_err());
+    }
+}
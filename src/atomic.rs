@@ -0,0 +1,194 @@
+use core::sync::atomic::Ordering;
+
+macro_rules! atomic {
+    ($atomic_name:ident, $atomic_inner:ident, $cap_name:ident, $inner:ident, $has_atomic:literal) => {
+        #[cfg(target_has_atomic = $has_atomic)]
+        #[doc = concat!("An atomic [`", stringify!($cap_name), "`] that can be safely shared between threads.")]
+        ///
+        /// Every operation takes and returns the capped type so the value always stays within `0..N`.
+        #[derive(Debug)]
+        pub struct $atomic_name<const N: $inner>(core::sync::atomic::$atomic_inner);
+
+        #[cfg(target_has_atomic = $has_atomic)]
+        impl<const N: $inner> $atomic_name<N> {
+            #[doc = concat!("Create a new [`Self`] from a [`", stringify!($cap_name), "`].")]
+            #[must_use]
+            pub const fn new(value: crate::$cap_name<N>) -> Self {
+                Self(core::sync::atomic::$atomic_inner::new(value.into_inner()))
+            }
+
+            /// Consumes the atomic and returns the contained value.
+            #[must_use]
+            pub fn into_inner(self) -> crate::$cap_name<N> {
+                crate::$cap_name::new_wrap(self.0.into_inner())
+            }
+
+            /// Loads the value.
+            ///
+            #[doc = concat!("See [`core::sync::atomic::", stringify!($atomic_inner), "::load`]")]
+            pub fn load(&self, order: Ordering) -> crate::$cap_name<N> {
+                crate::$cap_name::new_wrap(self.0.load(order))
+            }
+
+            /// Stores a value.
+            ///
+            #[doc = concat!("See [`core::sync::atomic::", stringify!($atomic_inner), "::store`]")]
+            pub fn store(&self, value: crate::$cap_name<N>, order: Ordering) {
+                self.0.store(value.into_inner(), order);
+            }
+
+            /// Stores a value, returning the previous value.
+            ///
+            #[doc = concat!("See [`core::sync::atomic::", stringify!($atomic_inner), "::swap`]")]
+            pub fn swap(&self, value: crate::$cap_name<N>, order: Ordering) -> crate::$cap_name<N> {
+                crate::$cap_name::new_wrap(self.0.swap(value.into_inner(), order))
+            }
+
+            /// Stores `new` if the current value is the same as `current`.
+            ///
+            /// The return value is a result indicating whether the new value was written and containing the previous value.
+            ///
+            #[doc = concat!("See [`core::sync::atomic::", stringify!($atomic_inner), "::compare_exchange`]")]
+            ///
+            /// # Errors
+            /// Will return `Err` containing the current value if it was not the same as `current`.
+            pub fn compare_exchange(
+                &self,
+                current: crate::$cap_name<N>,
+                new: crate::$cap_name<N>,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<crate::$cap_name<N>, crate::$cap_name<N>> {
+                self.0
+                    .compare_exchange(current.into_inner(), new.into_inner(), success, failure)
+                    .map(crate::$cap_name::new_wrap)
+                    .map_err(crate::$cap_name::new_wrap)
+            }
+
+            /// Fetches the value, and applies a function to it that returns an optional new value.
+            ///
+            #[doc = concat!("See [`core::sync::atomic::", stringify!($atomic_inner), "::fetch_update`]")]
+            ///
+            /// # Errors
+            /// Will return `Err` containing the previous value if the function returned [`None`].
+            pub fn fetch_update<F>(
+                &self,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                mut f: F,
+            ) -> Result<crate::$cap_name<N>, crate::$cap_name<N>>
+            where
+                F: FnMut(crate::$cap_name<N>) -> Option<crate::$cap_name<N>>,
+            {
+                self.0
+                    .fetch_update(set_order, fetch_order, |v| {
+                        f(crate::$cap_name::new_wrap(v)).map(crate::$cap_name::into_inner)
+                    })
+                    .map(crate::$cap_name::new_wrap)
+                    .map_err(crate::$cap_name::new_wrap)
+            }
+
+            /// Adds `rhs` to the current value using modulo `N`, returning the previous value.
+            pub fn fetch_wrapping_add(&self, rhs: $inner, order: Ordering) -> crate::$cap_name<N> {
+                let fetch_order = match order {
+                    Ordering::Release | Ordering::Relaxed => Ordering::Relaxed,
+                    Ordering::AcqRel | Ordering::Acquire => Ordering::Acquire,
+                    _ => Ordering::SeqCst,
+                };
+                match self.fetch_update(order, fetch_order, |v| Some(v.wrapping_add(rhs))) {
+                    Ok(v) | Err(v) => v,
+                }
+            }
+
+            /// Takes the current value and increments it by 1 wrapping if it exceeds the limit `N`.
+            ///
+            #[doc = concat!("See [`", stringify!($cap_name), "::take_increment`]")]
+            pub fn fetch_take_increment(&self, order: Ordering) -> crate::$cap_name<N> {
+                self.fetch_wrapping_add(1, order)
+            }
+        }
+
+        #[cfg(target_has_atomic = $has_atomic)]
+        impl<const N: $inner> From<crate::$cap_name<N>> for $atomic_name<N> {
+            fn from(value: crate::$cap_name<N>) -> Self {
+                Self::new(value)
+            }
+        }
+    };
+}
+
+atomic!(AtomicCapU8, AtomicU8, CapU8, u8, "8");
+atomic!(AtomicCapU16, AtomicU16, CapU16, u16, "16");
+atomic!(AtomicCapU32, AtomicU32, CapU32, u32, "32");
+atomic!(AtomicCapU64, AtomicU64, CapU64, u64, "64");
+atomic!(AtomicCapUsize, AtomicUsize, CapUsize, usize, "ptr");
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::Ordering;
+
+    use super::{AtomicCapU32, AtomicCapUsize};
+    use crate::{CapU32, CapUsize};
+
+    #[test]
+    fn load_store() {
+        let a = AtomicCapU32::<5>::new(CapU32::new_wrap(3));
+        assert_eq!(a.load(Ordering::SeqCst), 3);
+        a.store(CapU32::new_wrap(4), Ordering::SeqCst);
+        assert_eq!(a.swap(CapU32::new_wrap(1), Ordering::SeqCst), 4);
+        assert_eq!(
+            a.compare_exchange(
+                CapU32::new_wrap(0),
+                CapU32::new_wrap(2),
+                Ordering::SeqCst,
+                Ordering::SeqCst
+            ),
+            Err(CapU32::new_wrap(1))
+        );
+        assert_eq!(
+            a.compare_exchange(
+                CapU32::new_wrap(1),
+                CapU32::new_wrap(2),
+                Ordering::SeqCst,
+                Ordering::SeqCst
+            ),
+            Ok(CapU32::new_wrap(1))
+        );
+        assert_eq!(a.into_inner(), 2);
+    }
+
+    #[test]
+    fn wrapping() {
+        let a = AtomicCapU32::<3>::new(CapU32::new_wrap(1));
+        assert_eq!(a.fetch_take_increment(Ordering::Relaxed), 1);
+        assert_eq!(a.fetch_take_increment(Ordering::Relaxed), 2);
+        assert_eq!(a.fetch_take_increment(Ordering::Relaxed), 0);
+        assert_eq!(a.fetch_wrapping_add(5, Ordering::AcqRel), 1);
+        assert_eq!(a.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            a.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| None),
+            Err(CapU32::new_wrap(0))
+        );
+        assert_eq!(
+            a.fetch_update(Ordering::SeqCst, Ordering::SeqCst, CapU32::next),
+            Ok(CapU32::new_wrap(0))
+        );
+        assert_eq!(a.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn contended() {
+        let a = AtomicCapUsize::<7>::new(CapUsize::new_wrap(0));
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        let v = a.fetch_take_increment(Ordering::Relaxed);
+                        assert!(*v < 7);
+                    }
+                });
+            }
+        });
+        assert_eq!(a.load(Ordering::Relaxed), 4000 % 7);
+    }
+}
@@ -0,0 +1,409 @@
+use core::marker::PhantomData;
+
+use crate::CapNum;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A fixed size set of capped integers `K` stored inline as one bit per value.
+///
+/// Stable Rust can't size an array by `K::COUNT.div_ceil(64)` so the number of words is given as `W`,
+/// which must be equal to `ceil(K::COUNT / 64)`. This is checked at compile time.
+/// Every operation takes a `K` so no value can be out of range.
+///
+/// ```
+/// use capped::{CapBitSet, CapUsize};
+///
+/// let mut slots = CapBitSet::<CapUsize<100>, 2>::new();
+/// slots.insert(CapUsize::new_wrap(0));
+/// slots.insert(CapUsize::new_wrap(70));
+/// assert_eq!(slots.first_unset(), Some(CapUsize::new_wrap(1)));
+/// ```
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct CapBitSet<K, const W: usize> {
+    words: [u64; W],
+    marker: PhantomData<K>,
+}
+
+impl<K, const W: usize> CapBitSet<K, W>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    const VALID: () = assert!(
+        W == (K::COUNT + BITS - 1) / BITS,
+        "W must be equal to K::COUNT / 64 rounded up"
+    );
+
+    /// Create an empty [`CapBitSet`]
+    #[must_use]
+    pub const fn new() -> Self {
+        let () = Self::VALID;
+        Self {
+            words: [0; W],
+            marker: PhantomData,
+        }
+    }
+
+    /// Create a [`CapBitSet`] containing every value of `K`
+    #[must_use]
+    pub fn full() -> Self {
+        let mut set = Self::new();
+        set.words = [u64::MAX; W];
+        // Clear the bits in the last word that are not values of `K`
+        let used = K::COUNT % BITS;
+        if used != 0 {
+            if let Some(last) = set.words.last_mut() {
+                *last &= (1 << used) - 1;
+            }
+        }
+        set
+    }
+
+    fn index(key: K) -> usize {
+        key.into_inner()
+            .try_into()
+            .ok()
+            .expect("key is less than K::COUNT which fits in usize")
+    }
+
+    fn key(index: usize) -> Option<K> {
+        K::Inner::try_from(index)
+            .ok()
+            .and_then(|inner| K::try_from_inner(inner).ok())
+    }
+
+    /// The word holding the bit for `key` and the mask of that bit
+    fn position(key: K) -> (usize, u64) {
+        let index = Self::index(key);
+        (index / BITS, 1 << (index % BITS))
+    }
+
+    /// Adds a value to the set, returns whether the value was newly inserted.
+    pub fn insert(&mut self, key: K) -> bool {
+        let (word, mask) = Self::position(key);
+        let inserted = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        inserted
+    }
+
+    /// Removes a value from the set, returns whether the value was present.
+    pub fn remove(&mut self, key: K) -> bool {
+        let (word, mask) = Self::position(key);
+        let removed = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+        removed
+    }
+
+    /// Returns [`true`] if the set contains the value
+    #[must_use]
+    pub fn contains(&self, key: K) -> bool {
+        let (word, mask) = Self::position(key);
+        self.words[word] & mask != 0
+    }
+
+    /// Returns the number of values in the set
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns [`true`] if the set contains no values
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Removes every value from the set
+    pub fn clear(&mut self) {
+        self.words = [0; W];
+    }
+
+    /// Returns the smallest value in the set
+    #[must_use]
+    pub fn first(&self) -> Option<K> {
+        self.iter().next()
+    }
+
+    /// Returns the smallest value of `K` that is not in the set
+    #[must_use]
+    pub fn first_unset(&self) -> Option<K> {
+        self.first_unset_at(0)
+    }
+
+    /// Returns the smallest value that is at least `start` and is not in the set
+    ///
+    /// This scans 64 values at a time.
+    #[must_use]
+    pub fn first_unset_from(&self, start: K) -> Option<K> {
+        self.first_unset_at(Self::index(start))
+    }
+
+    fn first_unset_at(&self, start: usize) -> Option<K> {
+        // Treat the bits before `start` as set so they are skipped
+        let mut skip = (1u64 << (start % BITS)) - 1;
+        for (i, word) in self.words.iter().enumerate().skip(start / BITS) {
+            let free = !(word | skip);
+            skip = 0;
+            if free != 0 {
+                // The first unset bit may be past the last value in the last word
+                return Self::key(i * BITS + free.trailing_zeros() as usize);
+            }
+        }
+        None
+    }
+
+    /// Returns a new set containing the values in `self` or `other`
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    /// Returns a new set containing the values in both `self` and `other`
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    /// Returns a new set containing the values in `self` that are not in `other`
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & !b)
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        let mut set = Self::new();
+        for ((word, a), b) in set.words.iter_mut().zip(self.words).zip(other.words) {
+            *word = f(a, b);
+        }
+        set
+    }
+
+    /// Iterate over the values in the set in ascending order
+    #[must_use]
+    pub fn iter(&self) -> CapBitSetIter<'_, K> {
+        CapBitSetIter {
+            words: &self.words,
+            index: 0,
+            current: self.words.first().copied().unwrap_or(0),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, const W: usize> Default for CapBitSet<K, W>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, const W: usize> core::fmt::Debug for CapBitSet<K, W>
+where
+    K: CapNum + core::fmt::Debug,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K, const W: usize> FromIterator<K> for CapBitSet<K, W>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K, const W: usize> Extend<K> for CapBitSet<K, W>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+impl<'a, K, const W: usize> IntoIterator for &'a CapBitSet<K, W>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    type Item = K;
+    type IntoIter = CapBitSetIter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values in a [`CapBitSet`] in ascending order
+#[derive(Debug, Clone)]
+pub struct CapBitSetIter<'a, K> {
+    words: &'a [u64],
+    index: usize,
+    current: u64,
+    marker: PhantomData<K>,
+}
+
+impl<K> Iterator for CapBitSetIter<'_, K>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize>,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            self.index += 1;
+            self.current = *self.words.get(self.index)?;
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        // Clear the lowest set bit
+        self.current &= self.current - 1;
+        K::Inner::try_from(self.index * BITS + bit)
+            .ok()
+            .and_then(|inner| K::try_from_inner(inner).ok())
+    }
+}
+
+impl<K> core::iter::FusedIterator for CapBitSetIter<'_, K>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize>,
+{
+}
+
+#[cfg(feature = "serde")]
+impl<K, const W: usize> serde::Serialize for CapBitSet<K, W>
+where
+    K: CapNum + serde::Serialize,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, const W: usize> serde::Deserialize<'de> for CapBitSet<K, W>
+where
+    K: CapNum + serde::Deserialize<'de>,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Visitor;
+
+        struct CapBitSetVisitor<K, const W: usize>(PhantomData<K>);
+
+        impl<'de, K, const W: usize> Visitor<'de> for CapBitSetVisitor<K, W>
+        where
+            K: CapNum + serde::Deserialize<'de>,
+            K::Inner: TryFrom<usize> + TryInto<usize>,
+        {
+            type Value = CapBitSet<K, W>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                let end = K::COUNT;
+                formatter.write_fmt(format_args!("a sequence of integers in the range 0..{end}"))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut set = CapBitSet::new();
+                while let Some(key) = seq.next_element()? {
+                    set.insert(key);
+                }
+                Ok(set)
+            }
+        }
+        deserializer.deserialize_seq(CapBitSetVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CapBitSet;
+    use crate::{CapU8, CapUsize};
+
+    fn idx<const N: usize>(i: usize) -> CapUsize<N> {
+        CapUsize::try_from(i).unwrap()
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut set = CapBitSet::<CapUsize<100>, 2>::new();
+        assert!(set.is_empty());
+        assert!(set.insert(idx(3)));
+        assert!(!set.insert(idx(3)));
+        assert!(set.insert(idx(64)));
+        assert!(set.insert(idx(99)));
+        assert!(set.contains(idx(64)));
+        assert!(!set.contains(idx(65)));
+        assert_eq!(set.count(), 3);
+        assert_eq!(set.iter().map(|i| *i).collect::<Vec<_>>(), [3, 64, 99]);
+        assert_eq!(set.first(), Some(idx(3)));
+        assert!(set.remove(idx(64)));
+        assert!(!set.remove(idx(64)));
+        assert_eq!(format!("{set:?}"), "{CapUsize(3), CapUsize(99)}");
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(core::mem::size_of::<CapBitSet<CapUsize<100>, 2>>(), 16);
+    }
+
+    #[test]
+    fn first_unset() {
+        let mut set = CapBitSet::<CapUsize<70>, 2>::full();
+        assert_eq!(set.count(), 70);
+        assert_eq!(set.first_unset(), None);
+        set.remove(idx(65));
+        assert_eq!(set.first_unset(), Some(idx(65)));
+        set.remove(idx(2));
+        assert_eq!(set.first_unset(), Some(idx(2)));
+        assert_eq!(set.first_unset_from(idx(3)), Some(idx(65)));
+        assert_eq!(set.first_unset_from(idx(66)), None);
+        assert_eq!(CapBitSet::<CapUsize<0>, 0>::new().first_unset(), None);
+        assert_eq!(CapBitSet::<CapUsize<64>, 1>::full().first_unset(), None);
+    }
+
+    #[test]
+    fn set_ops() {
+        type Set = CapBitSet<CapU8<10>, 1>;
+        let key = |i| CapU8::new_checked(i).unwrap();
+        let a: Set = [1, 2, 3].into_iter().map(key).collect();
+        let b: Set = [3, 4].into_iter().map(key).collect();
+        let values = |s: &Set| s.iter().map(|i| *i).collect::<Vec<_>>();
+        assert_eq!(values(&a.union(&b)), [1, 2, 3, 4]);
+        assert_eq!(values(&a.intersection(&b)), [3]);
+        assert_eq!(values(&a.difference(&b)), [1, 2]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_bitset() -> serde_json::Result<()> {
+        type Set = CapBitSet<CapUsize<128>, 2>;
+        let set: Set = serde_json::from_str("[100, 5, 5, 70]")?;
+        assert_eq!(set.count(), 3);
+        assert_eq!(serde_json::to_string(&set)?, "[5,70,100]");
+        assert!(serde_json::from_str::<Set>("[128]").is_err());
+        Ok(())
+    }
+}
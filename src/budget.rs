@@ -0,0 +1,692 @@
+//! Limit the total size of a whole document while deserializing
+//!
+//! Capped types limit the size of a single value but nesting them multiplies the limits,
+//! a `CapVec<1000, CapVec<1000, CapString<1000>>>` can still hold a gigabyte of strings.
+//! [`Budgeted`] wraps any [`Deserializer`] and counts the total string bytes, sequence elements and map entries
+//! across the whole document, failing as soon as any total goes over its limit.
+//! It can also limit how deeply sequences and maps are nested, which stops recursive types from overflowing the stack,
+//! see [`CapDepth`] to limit the depth of a single value.
+//!
+//! ```
+//! use capped::budget::{Budgeted, Limits};
+//! use capped::{CapString, CapVec};
+//! use serde::Deserialize as _;
+//!
+//! let limits = Limits {
+//!     seq_elements: 3,
+//!     ..Limits::default()
+//! };
+//! let mut json = serde_json::Deserializer::from_str(r#"[["a", "b"], ["c", "d"]]"#);
+//! let result = CapVec::<10, CapVec<10, CapString<10>>>::deserialize(Budgeted::new(&mut json, limits));
+//! assert!(result.is_err());
+//! ```
+
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, Error, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+/// The limits on the totals across a whole document, each defaults to unlimited.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Limits {
+    /// The total length in bytes of all strings and byte arrays
+    pub string_bytes: usize,
+    /// The total number of elements in all sequences
+    pub seq_elements: usize,
+    /// The total number of entries in all maps
+    pub map_entries: usize,
+    /// The maximum number of sequences and maps, including structs and tuple and struct enum variants, nested inside each other
+    pub depth: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            string_bytes: usize::MAX,
+            seq_elements: usize::MAX,
+            map_entries: usize::MAX,
+            depth: usize::MAX,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    string_bytes: usize,
+    seq_elements: usize,
+    map_entries: usize,
+}
+
+#[derive(Debug)]
+struct State {
+    limits: Limits,
+    totals: Cell<Totals>,
+    depth: Cell<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    StringBytes,
+    SeqElements,
+    MapEntries,
+}
+
+impl State {
+    fn charge<E: Error>(&self, kind: Kind, n: usize) -> Result<(), E> {
+        let mut totals = self.totals.get();
+        let limits = self.limits;
+        let (total, limit) = match kind {
+            Kind::StringBytes => (&mut totals.string_bytes, limits.string_bytes),
+            Kind::SeqElements => (&mut totals.seq_elements, limits.seq_elements),
+            Kind::MapEntries => (&mut totals.map_entries, limits.map_entries),
+        };
+        *total = total.saturating_add(n);
+        let exceeded = *total > limit;
+        self.totals.set(totals);
+        if exceeded {
+            let Totals {
+                string_bytes,
+                seq_elements,
+                map_entries,
+            } = totals;
+            Err(E::custom(format_args!(
+                "deserialization budget exceeded, totals so far: \
+                 {string_bytes} string bytes (limit {}), \
+                 {seq_elements} sequence elements (limit {}), \
+                 {map_entries} map entries (limit {})",
+                limits.string_bytes, limits.seq_elements, limits.map_entries
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn enter<E: Error>(&self) -> Result<(), E> {
+        let depth = self.depth.get() + 1;
+        if depth > self.limits.depth {
+            Err(E::custom(format_args!(
+                "nesting depth exceeded the limit of {}",
+                self.limits.depth
+            )))
+        } else {
+            self.depth.set(depth);
+            Ok(())
+        }
+    }
+
+    fn leave(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+}
+
+/// A [`Deserializer`] that enforces [`Limits`] on the totals across everything it deserializes.
+///
+/// Create one with [`Budgeted::new`] around any deserializer and pass it to [`Deserialize::deserialize`](serde::Deserialize::deserialize).
+#[derive(Debug)]
+pub struct Budgeted<D> {
+    inner: D,
+    state: Rc<State>,
+}
+
+impl<D> Budgeted<D> {
+    /// Wrap a deserializer enforcing `limits` on the totals
+    pub fn new(inner: D, limits: Limits) -> Self {
+        Self {
+            inner,
+            state: Rc::new(State {
+                limits,
+                totals: Cell::new(Totals::default()),
+                depth: Cell::new(0),
+            }),
+        }
+    }
+
+    fn wrap<T>(&self, inner: T) -> Wrap<T> {
+        Wrap {
+            inner,
+            state: Rc::clone(&self.state),
+        }
+    }
+}
+
+/// Wraps the visitors, seeds and accessors passed between the deserializers so the totals are shared
+struct Wrap<T> {
+    inner: T,
+    state: Rc<State>,
+}
+
+impl<T> Wrap<T> {
+    fn wrap<U>(&self, inner: U) -> Wrap<U> {
+        Wrap {
+            inner,
+            state: Rc::clone(&self.state),
+        }
+    }
+
+    fn deserializer<U>(&self, inner: U) -> Budgeted<U> {
+        Budgeted {
+            inner,
+            state: Rc::clone(&self.state),
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method: ident($($arg: ident: $ty: ty),*),)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let visitor = self.wrap(visitor);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Budgeted<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_ignored_any(),
+    }
+
+    /// Identifiers such as struct field names are bounded by the type so they are not counted
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_identifier(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method: ident($ty: ty),)*) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+macro_rules! forward_visit_string {
+    ($($method: ident($ty: ty),)*) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                self.state.charge(Kind::StringBytes, v.len())?;
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Wrap<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+    }
+
+    forward_visit_string! {
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let deserializer = self.deserializer(deserializer);
+        self.inner.visit_some(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let deserializer = self.deserializer(deserializer);
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.state.enter()?;
+        let seq = self.wrap(seq);
+        let state = Rc::clone(&self.state);
+        let value = self.inner.visit_seq(seq);
+        state.leave();
+        value
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.state.enter()?;
+        let map = self.wrap(map);
+        let state = Rc::clone(&self.state);
+        let value = self.inner.visit_map(map);
+        state.leave();
+        value
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let data = self.wrap(data);
+        self.inner.visit_enum(data)
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Wrap<S> {
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let deserializer = self.deserializer(deserializer);
+        self.inner.deserialize(deserializer)
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        let element = self.inner.next_element_seed(seed)?;
+        if element.is_some() {
+            self.state.charge(Kind::SeqElements, 1)?;
+        }
+        Ok(element)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        let key = self.inner.next_key_seed(seed)?;
+        if key.is_some() {
+            self.state.charge(Kind::MapEntries, 1)?;
+        }
+        Ok(key)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+    type Variant = Wrap<A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        // The variant name is an identifier so it is not wrapped
+        let (value, variant) = self.inner.variant_seed(seed)?;
+        Ok((
+            value,
+            Wrap {
+                inner: variant,
+                state: self.state,
+            },
+        ))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        self.inner.newtype_variant_seed(seed)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let visitor = self.wrap(visitor);
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let visitor = self.wrap(visitor);
+        self.inner.struct_variant(fields, visitor)
+    }
+}
+
+/// A wrapper that limits how deeply sequences and maps are nested while deserializing `T`.
+///
+/// The depth counts sequences, maps, structs, and tuple and struct enum variants, so a `CapDepth<1, Vec<u8>>` accepts `[1, 2]` but not `[[1]]`.
+/// This stops recursive types from overflowing the stack, other limits are left to the inner deserializer.
+///
+/// ```
+/// use capped::{CapDepth, CapVec};
+///
+/// #[derive(serde::Deserialize)]
+/// enum Expr {
+///     Num(u8),
+///     List(CapVec<16, Expr>),
+/// }
+///
+/// let shallow = r#"{"List": [{"Num": 1}]}"#;
+/// assert!(serde_json::from_str::<CapDepth<2, Expr>>(shallow).is_ok());
+/// let deep = r#"{"List": [{"List": [{"List": [{"List": []}]}]}]}"#;
+/// assert!(serde_json::from_str::<CapDepth<2, Expr>>(deep).is_err());
+/// ```
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CapDepth<const D: usize, T>(T);
+
+impl<const D: usize, T> CapDepth<D, T> {
+    /// Wrap a value
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns the inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<const D: usize, T> core::ops::Deref for CapDepth<D, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const D: usize, T> core::ops::DerefMut for CapDepth<D, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const D: usize, T: serde::Serialize> serde::Serialize for CapDepth<D, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, const D: usize, T: serde::Deserialize<'de>> serde::Deserialize<'de> for CapDepth<D, T> {
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        let limits = Limits {
+            depth: D,
+            ..Limits::default()
+        };
+        T::deserialize(Budgeted::new(deserializer, limits)).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize as _;
+
+    use super::{Budgeted, CapDepth, Limits};
+    use crate::{CapString, CapVec};
+
+    type Nested = CapVec<100, CapVec<100, CapString<100>>>;
+
+    fn deserialize<'de, T: serde::Deserialize<'de>>(
+        json: &'de str,
+        limits: Limits,
+    ) -> serde_json::Result<T> {
+        let mut de = serde_json::Deserializer::from_str(json);
+        T::deserialize(Budgeted::new(&mut de, limits))
+    }
+
+    #[test]
+    fn within_budget() -> serde_json::Result<()> {
+        let limits = Limits {
+            string_bytes: 4,
+            seq_elements: 6,
+            map_entries: 0,
+            depth: 2,
+        };
+        let v: Nested = deserialize(r#"[["a", "b"], ["c", "d"]]"#, limits)?;
+        assert_eq!(v.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn string_bytes() {
+        let limits = Limits {
+            string_bytes: 5,
+            ..Limits::default()
+        };
+        let err = deserialize::<Nested>(r#"[["abc"], ["def"]]"#, limits).unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "deserialization budget exceeded, totals so far: 6 string bytes (limit 5), 2 sequence elements"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn seq_elements() {
+        let limits = Limits {
+            seq_elements: 3,
+            ..Limits::default()
+        };
+        let err = deserialize::<Nested>(r#"[["a", "b"], ["c"]]"#, limits).unwrap_err();
+        assert!(
+            err.to_string().contains("4 sequence elements (limit 3)"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn map_entries() {
+        let limits = Limits {
+            map_entries: 2,
+            ..Limits::default()
+        };
+        let json = r#"[{"a": 1, "b": 2}, {"c": 3}]"#;
+        let err = deserialize::<Vec<HashMap<String, u8>>>(json, limits).unwrap_err();
+        assert!(err.to_string().contains("3 map entries (limit 2)"), "{err}");
+    }
+
+    #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+    enum Shape {
+        Circle { radius: u8 },
+        Tags(CapVec<3, CapString<3>>),
+    }
+
+    #[test]
+    fn enums() -> serde_json::Result<()> {
+        let limits = Limits {
+            string_bytes: 3,
+            ..Limits::default()
+        };
+        let shapes: Vec<Shape> =
+            deserialize(r#"[{"Circle": {"radius": 1}}, {"Tags": ["abc"]}]"#, limits)?;
+        assert_eq!(shapes[0], Shape::Circle { radius: 1 });
+        let tags =
+            CapVec::try_from(vec![CapString::try_from(String::from("abc")).unwrap()]).unwrap();
+        assert_eq!(shapes[1], Shape::Tags(tags));
+        let mut de = serde_json::Deserializer::from_str(r#"{"Tags": ["abc", "d"]}"#);
+        assert!(Shape::deserialize(Budgeted::new(&mut de, limits)).is_err());
+        Ok(())
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    enum Expr {
+        Num(u8),
+        List(CapVec<16, Self>),
+    }
+
+    impl Expr {
+        fn sum(&self) -> u32 {
+            match self {
+                Self::Num(n) => u32::from(*n),
+                Self::List(list) => list.as_ref().iter().map(Self::sum).sum(),
+            }
+        }
+    }
+
+    fn nested(depth: usize) -> String {
+        let mut json = String::from(r#"{"Num": 1}"#);
+        for _ in 0..depth {
+            json = format!(r#"{{"List": [{json}]}}"#);
+        }
+        json
+    }
+
+    #[test]
+    fn depth() -> serde_json::Result<()> {
+        // Newtype variants are not counted so each level is the sequence in the list
+        let expr: CapDepth<20, Expr> = serde_json::from_str(&nested(20))?;
+        assert_eq!(expr.sum(), 1);
+        let err = serde_json::from_str::<CapDepth<20, Expr>>(&nested(21)).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("nesting depth exceeded the limit of 20"),
+            "{err}"
+        );
+
+        let limits = Limits {
+            depth: 2,
+            ..Limits::default()
+        };
+        assert!(deserialize::<Nested>(r#"[["a"]]"#, limits).is_ok());
+        assert!(deserialize::<Vec<Vec<Vec<u8>>>>("[[[]]]", limits).is_err());
+        // The depth is restored after leaving each sequence
+        assert!(deserialize::<Vec<Vec<u8>>>("[[], [], [1]]", limits).is_ok());
+
+        let v: CapDepth<1, Vec<u8>> = serde_json::from_str("[1, 2]")?;
+        assert_eq!(serde_json::to_string(&v)?, "[1,2]");
+        Ok(())
+    }
+}
@@ -0,0 +1,808 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A byte buffer with a limit on its length that serializes as bytes, its length must be in the range `0..=N`.
+///
+/// Unlike [`CapVec<N, u8>`](crate::CapVec) this uses the bytes support of the serde data model, so binary formats store it compactly.
+/// Human readable formats store it as a base64 string, use [`capped::hex`](crate::hex) to store it as a hex string instead.
+///
+/// Writing past the cap with [`std::io::Write`] fails with [`std::io::ErrorKind::WriteZero`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CapBytes<const N: usize>(Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> CapBytes<N> {
+    /// Create an empty [`CapBytes`]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Gets the number of bytes
+    ///
+    /// Guaranteed to be in the range `0..=N`.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns [`true`] if there are no bytes
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Extracts a slice containing all the bytes
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Pushes a byte on to the end if there is space returns [`None`], otherwise returns [`Some`] `byte`.
+    ///
+    /// See [`Vec::push`]
+    #[must_use]
+    pub fn push(&mut self, byte: u8) -> Option<u8> {
+        if self.0.len() < N {
+            self.0.push(byte);
+            None
+        } else {
+            Some(byte)
+        }
+    }
+
+    /// Pushes a byte on to the end if there is space, otherwise returns `byte` as the error.
+    ///
+    /// See [`Vec::push`]
+    /// # Errors
+    /// Will return `Err` with `byte` if the bytes already hold `N` bytes.
+    pub fn try_push(&mut self, byte: u8) -> Result<(), u8> {
+        self.push(byte).map_or(Ok(()), Err)
+    }
+
+    /// Appends all the bytes if they fit within the cap.
+    ///
+    /// See [`Vec::extend_from_slice`]
+    /// # Errors
+    /// Will return `Err` if the new length would be greater than the cap `N`.
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), CapBytesLengthError<N>> {
+        let len = self.0.len() + bytes.len();
+        if len <= N {
+            self.0.extend_from_slice(bytes);
+            Ok(())
+        } else {
+            Err(CapBytesLengthError(len))
+        }
+    }
+
+    /// Clears the bytes setting the length to `0`.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Truncate to `len` bytes.
+    ///
+    /// See [`Vec::truncate`]
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    /// Get the underlying [`Vec`]
+    #[must_use]
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> AsRef<[u8]> for CapBytes<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> core::ops::Deref for CapBytes<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> core::ops::DerefMut for CapBytes<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<CapBytes<N>> for Vec<u8> {
+    fn from(value: CapBytes<N>) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> TryFrom<Vec<u8>> for CapBytes<N> {
+    type Error = CapBytesLengthError<N>;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.len() <= N {
+            Ok(Self(value))
+        } else {
+            Err(CapBytesLengthError(value.len()))
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> TryFrom<&[u8]> for CapBytes<N> {
+    type Error = CapBytesLengthError<N>;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut bytes = Self::new();
+        bytes.extend_from_slice(value)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> std::io::Write for CapBytes<N> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(N - self.0.len());
+        if n == 0 && !buf.is_empty() {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        self.0.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A byte buffer stored inline in an array `[u8; N]`, its length must be in the range `0..=N`.
+///
+/// Serializes the same way as [`CapBytes`].
+#[derive(Clone, Copy)]
+pub struct CapByteArray<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> CapByteArray<N> {
+    /// Create an empty [`CapByteArray`]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Gets the number of bytes
+    ///
+    /// Guaranteed to be in the range `0..=N`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns [`true`] if there are no bytes
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Extracts a slice containing all the bytes
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Extracts a mutable slice containing all the bytes
+    #[must_use]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.buf[..self.len]
+    }
+
+    /// Pushes a byte on to the end if there is space returns [`None`], otherwise returns [`Some`] `byte`.
+    #[must_use]
+    pub fn push(&mut self, byte: u8) -> Option<u8> {
+        if self.len < N {
+            self.buf[self.len] = byte;
+            self.len += 1;
+            None
+        } else {
+            Some(byte)
+        }
+    }
+
+    /// Pushes a byte on to the end if there is space, otherwise returns `byte` as the error.
+    ///
+    /// # Errors
+    /// Will return `Err` with `byte` if the array already holds `N` bytes.
+    pub fn try_push(&mut self, byte: u8) -> Result<(), u8> {
+        self.push(byte).map_or(Ok(()), Err)
+    }
+
+    /// Removes the last byte and returns it.
+    pub fn pop(&mut self) -> Option<u8> {
+        self.len = self.len.checked_sub(1)?;
+        Some(self.buf[self.len])
+    }
+
+    /// Appends all the bytes if they fit within the cap.
+    ///
+    /// # Errors
+    /// Will return `Err` if the new length would be greater than the cap `N`.
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), CapBytesLengthError<N>> {
+        let len = self.len + bytes.len();
+        if len <= N {
+            self.buf[self.len..len].copy_from_slice(bytes);
+            self.len = len;
+            Ok(())
+        } else {
+            Err(CapBytesLengthError(len))
+        }
+    }
+
+    /// Clears the bytes setting the length to `0`.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Truncate to `len` bytes.
+    ///
+    /// If `len` is greater than or equal to the current length, this has no effect.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+}
+
+impl<const N: usize> Default for CapByteArray<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for CapByteArray<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CapByteArray")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
+
+impl<const N: usize> PartialEq for CapByteArray<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for CapByteArray<N> {}
+
+impl<const N: usize> core::hash::Hash for CapByteArray<N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for CapByteArray<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> core::ops::Deref for CapByteArray<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> core::ops::DerefMut for CapByteArray<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for CapByteArray<N> {
+    type Error = CapBytesLengthError<N>;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut bytes = Self::new();
+        bytes.extend_from_slice(value)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> std::io::Write for CapByteArray<N> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(N - self.len);
+        if n == 0 && !buf.is_empty() {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        self.buf[self.len..self.len + n].copy_from_slice(&buf[..n]);
+        self.len += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Error returned when bytes would be longer than N
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapBytesLengthError<const N: usize>(usize);
+
+impl<const N: usize> CapBytesLengthError<N> {
+    /// The length that was attempted
+    #[must_use]
+    pub const fn actual(&self) -> usize {
+        self.0
+    }
+
+    /// The cap `N` the length must not exceed
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        N
+    }
+
+    /// Returns [`ErrorKind::TooLong`](crate::ErrorKind::TooLong)
+    #[must_use]
+    pub const fn kind(&self) -> crate::ErrorKind {
+        crate::ErrorKind::TooLong
+    }
+}
+
+impl<const N: usize> core::fmt::Display for CapBytesLengthError<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let len = self.0;
+        f.write_fmt(format_args!(
+            "cap bytes length error, length {len} is longer than {N}"
+        ))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> std::error::Error for CapBytesLengthError<N> {}
+
+/// A byte buffer with a maximum length, implemented by [`CapBytes`] and [`CapByteArray`]
+pub trait CapByteBuf: AsRef<[u8]> + Default {
+    /// The maximum number of bytes
+    const CAP: usize;
+
+    /// Appends all the bytes if they fit within the cap, otherwise returns the length it would have reached.
+    ///
+    /// # Errors
+    /// Will return `Err` if the new length would be greater than [`CapByteBuf::CAP`].
+    fn try_append(&mut self, bytes: &[u8]) -> Result<(), usize>;
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> CapByteBuf for CapBytes<N> {
+    const CAP: usize = N;
+
+    fn try_append(&mut self, bytes: &[u8]) -> Result<(), usize> {
+        self.extend_from_slice(bytes).map_err(|err| err.actual())
+    }
+}
+
+impl<const N: usize> CapByteBuf for CapByteArray<N> {
+    const CAP: usize = N;
+
+    fn try_append(&mut self, bytes: &[u8]) -> Result<(), usize> {
+        self.extend_from_slice(bytes).map_err(|err| err.actual())
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use serde_impls::{deserialize, serialize, Encoding};
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    #[cfg(feature = "alloc")]
+    use alloc::vec::Vec;
+    use core::marker::PhantomData;
+
+    use serde::de::{Deserializer, Error, SeqAccess, Unexpected, Visitor};
+    use serde::{Deserialize, Serialize, Serializer};
+
+    #[cfg(feature = "alloc")]
+    use super::CapBytes;
+    use super::{CapByteArray, CapByteBuf};
+
+    const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    /// How bytes are written as a string in human readable formats
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Encoding {
+        /// Standard base64 with padding
+        Base64,
+        /// Lowercase hex, uppercase is also accepted when decoding
+        Hex,
+    }
+
+    struct Encoded<'a> {
+        bytes: &'a [u8],
+        encoding: Encoding,
+    }
+
+    impl core::fmt::Display for Encoded<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            let mut out = [0; 4];
+            match self.encoding {
+                Encoding::Base64 => {
+                    for chunk in self.bytes.chunks(3) {
+                        let b = [
+                            chunk[0],
+                            chunk.get(1).copied().unwrap_or(0),
+                            chunk.get(2).copied().unwrap_or(0),
+                        ];
+                        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+                        for (i, c) in out.iter_mut().enumerate() {
+                            *c = if i <= chunk.len() {
+                                BASE64[(n >> (18 - 6 * i)) as usize & 63]
+                            } else {
+                                b'='
+                            };
+                        }
+                        f.write_str(core::str::from_utf8(&out).map_err(|_| core::fmt::Error)?)?;
+                    }
+                }
+                Encoding::Hex => {
+                    for &b in self.bytes {
+                        out[0] = HEX[usize::from(b >> 4)];
+                        out[1] = HEX[usize::from(b & 15)];
+                        f.write_str(
+                            core::str::from_utf8(&out[..2]).map_err(|_| core::fmt::Error)?,
+                        )?;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    enum DecodeError {
+        Invalid,
+        TooLong(usize),
+    }
+
+    impl From<usize> for DecodeError {
+        fn from(len: usize) -> Self {
+            Self::TooLong(len)
+        }
+    }
+
+    fn decode<T: CapByteBuf>(encoding: Encoding, string: &str) -> Result<T, DecodeError> {
+        let mut out = T::default();
+        match encoding {
+            Encoding::Base64 => {
+                let input = string.as_bytes();
+                let data = input
+                    .strip_suffix(b"==")
+                    .or_else(|| input.strip_suffix(b"="))
+                    .unwrap_or(input);
+                if (data.len() != input.len() && input.len() % 4 != 0) || data.len() % 4 == 1 {
+                    return Err(DecodeError::Invalid);
+                }
+                let len = data.len() / 4 * 3 + (data.len() % 4).saturating_sub(1);
+                if len > T::CAP {
+                    return Err(DecodeError::TooLong(len));
+                }
+                for chunk in data.chunks(4) {
+                    let mut acc = 0;
+                    for (i, &c) in chunk.iter().enumerate() {
+                        let value = (0..)
+                            .zip(BASE64)
+                            .find_map(|(value, &b)| (b == c).then_some(value))
+                            .ok_or(DecodeError::Invalid)?;
+                        acc |= value << (18 - 6 * i);
+                    }
+                    let [_, first, second, third] = u32::to_be_bytes(acc);
+                    out.try_append(&[first, second, third][..chunk.len() - 1])?;
+                }
+            }
+            Encoding::Hex => {
+                let input = string.as_bytes();
+                if input.len() % 2 != 0 {
+                    return Err(DecodeError::Invalid);
+                }
+                if input.len() / 2 > T::CAP {
+                    return Err(DecodeError::TooLong(input.len() / 2));
+                }
+                for pair in input.chunks(2) {
+                    let digit = |c: u8| char::from(c).to_digit(16).ok_or(DecodeError::Invalid);
+                    let byte = digit(pair[0])? << 4 | digit(pair[1])?;
+                    out.try_append(&[u8::try_from(byte).map_err(|_| DecodeError::Invalid)?])?;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    struct BytesVisitor<T> {
+        encoding: Encoding,
+        marker: PhantomData<T>,
+    }
+
+    impl<'de, T: CapByteBuf> Visitor<'de> for BytesVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            let encoding = match self.encoding {
+                Encoding::Base64 => "base64",
+                Encoding::Hex => "hex",
+            };
+            formatter.write_fmt(format_args!(
+                "bytes or a {encoding} string of at most {} bytes",
+                T::CAP
+            ))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            decode(self.encoding, v).map_err(|err| match err {
+                DecodeError::Invalid => E::invalid_value(Unexpected::Str(v), &self),
+                DecodeError::TooLong(len) => E::invalid_length(len, &self),
+            })
+        }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            let mut out = T::default();
+            out.try_append(v)
+                .map_err(|len| E::invalid_length(len, &self))?;
+            Ok(out)
+        }
+
+        fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            self.visit_bytes(v)
+        }
+
+        #[cfg(feature = "alloc")]
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            self.visit_bytes(&v)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            if let Some(size) = seq.size_hint().filter(|size| *size > T::CAP) {
+                return Err(A::Error::invalid_length(size, &self));
+            }
+            let mut out = T::default();
+            let mut len = 0;
+            while let Some(byte) = seq.next_element::<u8>()? {
+                len += 1;
+                out.try_append(&[byte])
+                    .map_err(|_| A::Error::invalid_length(len, &self))?;
+            }
+            Ok(out)
+        }
+    }
+
+    /// Serialize bytes with `encoding` in human readable formats and as plain bytes otherwise
+    ///
+    /// # Errors
+    /// Will return `Err` if the serializer fails.
+    pub fn serialize<S>(bytes: &[u8], encoding: Encoding, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&Encoded { bytes, encoding })
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    /// Deserialize bytes written by [`serialize`]
+    ///
+    /// # Errors
+    /// Will return `Err` if the input is not valid bytes or is longer than the cap.
+    pub fn deserialize<'de, D, T>(deserializer: D, encoding: Encoding) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: CapByteBuf,
+    {
+        let visitor = BytesVisitor {
+            encoding,
+            marker: PhantomData,
+        };
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(visitor)
+        } else {
+            deserializer.deserialize_bytes(visitor)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<const N: usize> Serialize for CapBytes<N> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize(&self.0, Encoding::Base64, serializer)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<'de, const N: usize> Deserialize<'de> for CapBytes<N> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserialize(deserializer, Encoding::Base64)
+        }
+    }
+
+    impl<const N: usize> Serialize for CapByteArray<N> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize(self.as_slice(), Encoding::Base64, serializer)
+        }
+    }
+
+    impl<'de, const N: usize> Deserialize<'de> for CapByteArray<N> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserialize(deserializer, Encoding::Base64)
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use std::io::Write as _;
+
+    use super::{CapByteArray, CapBytes, CapBytesLengthError};
+
+    #[test]
+    fn manipulate_bytes() {
+        let mut b = CapBytes::<4>::try_from(&b"ab"[..]).unwrap();
+        assert_eq!(b.push(b'c'), None);
+        assert_eq!(b.extend_from_slice(b"de"), Err(CapBytesLengthError(5)));
+        assert_eq!(b.extend_from_slice(b"d"), Ok(()));
+        assert_eq!(b.push(b'e'), Some(b'e'));
+        assert_eq!(b.try_push(b'e'), Err(b'e'));
+        assert_eq!(b.as_slice(), b"abcd");
+        b.truncate(1);
+        assert_eq!(Vec::from(b), b"a");
+        assert!(CapBytes::<1>::try_from(vec![1, 2]).is_err());
+
+        let mut a = CapByteArray::<4>::new();
+        assert!(a.is_empty());
+        a.extend_from_slice(b"abc").unwrap();
+        assert_eq!(a.push(b'd'), None);
+        assert_eq!(a.push(b'e'), Some(b'e'));
+        assert_eq!(a.try_push(b'e'), Err(b'e'));
+        assert_eq!(a.pop(), Some(b'd'));
+        assert_eq!(a.try_push(b'd'), Ok(()));
+        assert_eq!(a.pop(), Some(b'd'));
+        a[0] = b'z';
+        assert_eq!(&*a, b"zbc");
+        // Bytes past the length are not compared
+        a.truncate(1);
+        assert_eq!(a, CapByteArray::try_from(&b"z"[..]).unwrap());
+        a.clear();
+        assert_eq!(a.pop(), None);
+        assert_eq!(
+            CapByteArray::<2>::try_from(&b"abc"[..])
+                .unwrap_err()
+                .to_string(),
+            "cap bytes length error, length 3 is longer than 2"
+        );
+    }
+
+    #[test]
+    fn io_write() {
+        let mut b = CapBytes::<5>::new();
+        write!(b, "abc").unwrap();
+        assert_eq!(b.write(b"def").unwrap(), 2);
+        let err = b.write_all(b"g").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(b.write(b"").unwrap(), 0);
+        assert_eq!(b.as_slice(), b"abcde");
+
+        let mut a = CapByteArray::<3>::new();
+        let err = a.write_all(b"abcd").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(a.as_slice(), b"abc");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_bytes() {
+        for (bytes, base64) in [
+            (&b""[..], r#""""#),
+            (b"f", r#""Zg==""#),
+            (b"fo", r#""Zm8=""#),
+            (b"foo", r#""Zm9v""#),
+            (b"foob", r#""Zm9vYg==""#),
+            (b"\xff\xfe\x00", r#""//4A""#),
+        ] {
+            let b = CapBytes::<4>::try_from(bytes).unwrap();
+            assert_eq!(serde_json::to_string(&b).unwrap(), base64);
+            assert_eq!(serde_json::from_str::<CapBytes<4>>(base64).unwrap(), b);
+            let a = CapByteArray::<4>::try_from(bytes).unwrap();
+            assert_eq!(serde_json::to_string(&a).unwrap(), base64);
+            assert_eq!(serde_json::from_str::<CapByteArray<4>>(base64).unwrap(), a);
+        }
+        // Padding is optional
+        assert_eq!(
+            serde_json::from_str::<CapBytes<4>>(r#""Zm8""#)
+                .unwrap()
+                .as_slice(),
+            b"fo"
+        );
+
+        let err = serde_json::from_str::<CapBytes<2>>(r#""Zm9v""#).unwrap_err();
+        assert!(err.to_string().starts_with("invalid length 3"), "{err}");
+        for invalid in [r#""Z""#, r#""Zm!v""#, r#""Zg=""#, r#""Zg===""#] {
+            assert!(
+                serde_json::from_str::<CapBytes<4>>(invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_bytes_binary() {
+        let b = CapBytes::<4>::try_from(&b"abc"[..]).unwrap();
+        let encoded = rmp_serde::to_vec(&b).unwrap();
+        // Stored as a msgpack bin 8 not an array of integers
+        assert_eq!(encoded, b"\xc4\x03abc");
+        assert_eq!(rmp_serde::from_slice::<CapBytes<4>>(&encoded).unwrap(), b);
+        assert_eq!(
+            rmp_serde::from_slice::<CapByteArray<3>>(&encoded)
+                .unwrap()
+                .as_slice(),
+            b"abc"
+        );
+        assert!(rmp_serde::from_slice::<CapBytes<2>>(&encoded).is_err());
+
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&b, &mut cbor).unwrap();
+        assert_eq!(cbor, b"\x43abc");
+        let from_cbor: CapByteArray<4> = ciborium::from_reader(&cbor[..]).unwrap();
+        assert_eq!(from_cbor.as_slice(), b"abc");
+
+        // A sequence of integers is also accepted
+        let seq = rmp_serde::to_vec(&[1u8, 2, 3]).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<CapBytes<3>>(&seq)
+                .unwrap()
+                .as_slice(),
+            [1, 2, 3]
+        );
+        assert!(rmp_serde::from_slice::<CapBytes<2>>(&seq).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Helper functions for use with `#[serde(deserialize_with = "...")]`
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use serde::de::{Deserializer, Error, MapAccess, Visitor};
+use serde::Deserialize;
+
+use crate::num::CapNum;
+#[cfg(feature = "alloc")]
+use crate::{CapString, CapVec};
+
+/// Deserialize a capped integer, clamping out of range values to the nearest bound instead of failing.
+///
+/// Negative values become `0` and values that are too large become `N - 1`.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     #[serde(deserialize_with = "capped::de::clamp")]
+///     volume: capped::CapU8<101>,
+/// }
+///
+/// let config: Config = serde_json::from_str(r#"{"volume": 250}"#).unwrap();
+/// assert_eq!(config.volume, 100);
+/// ```
+///
+/// # Errors
+/// Will return `Err` if the input is not an integer.
+///
+/// # Panics
+/// Panics if `T` can hold no values.
+pub fn clamp<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: CapNum,
+    T::Inner: TryFrom<u128>,
+{
+    struct ClampVisitor<T>(PhantomData<T>);
+
+    impl<T> ClampVisitor<T>
+    where
+        T: CapNum,
+        T::Inner: TryFrom<u128>,
+    {
+        fn clamp(v: u128) -> T {
+            let max = (T::COUNT as u128)
+                .checked_sub(1)
+                .expect("cannot clamp to a type that can hold no values");
+            T::new_wrap(
+                T::Inner::try_from(v.min(max))
+                    .ok()
+                    .expect("value is less than T::COUNT"),
+            )
+        }
+    }
+
+    impl<T> Visitor<'_> for ClampVisitor<T>
+    where
+        T: CapNum,
+        T::Inner: TryFrom<u128>,
+    {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("an integer")
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            self.visit_i128(i128::from(v))
+        }
+
+        fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(Self::clamp(u128::try_from(v).unwrap_or(0)))
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(Self::clamp(u128::from(v)))
+        }
+
+        fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(Self::clamp(v))
+        }
+    }
+
+    T::__deserialize_inner(deserializer, ClampVisitor(PhantomData))
+}
+
+/// Deserialize a [`Vec`] with at most `N` elements, using the same checks as [`CapVec`].
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Batch {
+///     #[serde(deserialize_with = "capped::de::vec::<2, _, _>")]
+///     items: Vec<u32>,
+/// }
+///
+/// let batch: Batch = serde_json::from_str(r#"{"items": [1, 2]}"#).unwrap();
+/// assert_eq!(batch.items, [1, 2]);
+/// assert!(serde_json::from_str::<Batch>(r#"{"items": [1, 2, 3]}"#).is_err());
+/// ```
+///
+/// # Errors
+/// Will return `Err` if the input is not a sequence or has more than `N` elements.
+#[cfg(feature = "alloc")]
+pub fn vec<'de, const N: usize, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    CapVec::<N, T>::deserialize(deserializer).map(CapVec::into_inner)
+}
+
+/// Deserialize a [`String`] with at most `N` bytes, using the same checks as [`CapString`].
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct User {
+///     #[serde(deserialize_with = "capped::de::string::<5, _>")]
+///     name: String,
+/// }
+///
+/// let user: User = serde_json::from_str(r#"{"name": "alice"}"#).unwrap();
+/// assert_eq!(user.name, "alice");
+/// assert!(serde_json::from_str::<User>(r#"{"name": "bobby!"}"#).is_err());
+/// ```
+///
+/// # Errors
+/// Will return `Err` if the input is not a string or is longer than `N` bytes.
+#[cfg(feature = "alloc")]
+pub fn string<'de, const N: usize, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    CapString::<N>::deserialize(deserializer).map(CapString::into_inner)
+}
+
+/// Deserialize a map such as a [`HashMap`](std::collections::HashMap) or [`BTreeMap`](std::collections::BTreeMap) with at most `N` entries.
+///
+/// Fails as soon as the input has more than `N` entries.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Labels {
+///     #[serde(deserialize_with = "capped::de::map::<2, _, _, _, _>")]
+///     labels: std::collections::HashMap<String, String>,
+/// }
+///
+/// let labels: Labels = serde_json::from_str(r#"{"labels": {"a": "1"}}"#).unwrap();
+/// assert_eq!(labels.labels["a"], "1");
+/// assert!(serde_json::from_str::<Labels>(r#"{"labels": {"a": "1", "b": "2", "c": "3"}}"#).is_err());
+/// ```
+///
+/// # Errors
+/// Will return `Err` if the input is not a map or has more than `N` entries.
+pub fn map<'de, const N: usize, D, M, K, V>(deserializer: D) -> Result<M, D::Error>
+where
+    D: Deserializer<'de>,
+    M: Default + Extend<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    struct MapVisitor<const N: usize, M, K, V>(PhantomData<(M, K, V)>);
+
+    impl<'de, const N: usize, M, K, V> Visitor<'de> for MapVisitor<N, M, K, V>
+    where
+        M: Default + Extend<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = M;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_fmt(format_args!("a map with at most {N} entries"))
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            if let Some(size) = map.size_hint().filter(|size| *size > N) {
+                return Err(A::Error::invalid_length(size, &self));
+            }
+            let mut values = M::default();
+            let mut len = 0;
+            while let Some(entry) = map.next_entry()? {
+                len += 1;
+                if len > N {
+                    return Err(A::Error::invalid_length(len, &self));
+                }
+                values.extend(Some(entry));
+            }
+            Ok(values)
+        }
+    }
+
+    deserializer.deserialize_map(MapVisitor::<N, M, K, V>(PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CapU16, CapU32, CapU64, CapU8};
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Config {
+        #[serde(deserialize_with = "super::clamp")]
+        volume: CapU8<101>,
+        #[serde(deserialize_with = "super::clamp")]
+        page_size: CapU16<500>,
+    }
+
+    #[test]
+    fn clamp() -> serde_json::Result<()> {
+        let c: Config = serde_json::from_str(r#"{"volume": 50, "page_size": 20}"#)?;
+        assert_eq!(c.volume, 50);
+        assert_eq!(c.page_size, 20);
+
+        let c: Config = serde_json::from_str(r#"{"volume": 250, "page_size": 100000}"#)?;
+        assert_eq!(c.volume, 100);
+        assert_eq!(c.page_size, 499);
+
+        let c: Config =
+            serde_json::from_str(r#"{"volume": -5, "page_size": 18446744073709551615}"#)?;
+        assert_eq!(c.volume, 0);
+        assert_eq!(c.page_size, 499);
+
+        assert!(serde_json::from_str::<Config>(r#"{"volume": "a", "page_size": 1}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn clamp_not_self_describing() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Limits {
+            #[serde(deserialize_with = "super::clamp")]
+            volume: CapU8<101>,
+            #[serde(deserialize_with = "super::clamp")]
+            page_size: CapU16<500>,
+            #[serde(deserialize_with = "super::clamp")]
+            retries: CapU32<10>,
+            #[serde(deserialize_with = "super::clamp")]
+            offset: CapU64<1000>,
+        }
+
+        let limits = Limits {
+            volume: CapU8::new_wrap(100),
+            page_size: CapU16::new_wrap(20),
+            retries: CapU32::new_wrap(3),
+            offset: CapU64::new_wrap(999),
+        };
+        // bincode writes each integer with its own width and can't tell what it holds
+        let bytes = bincode::serialize(&limits).unwrap();
+        assert_eq!(bytes.len(), 1 + 2 + 4 + 8);
+        assert_eq!(bincode::deserialize::<Limits>(&bytes).unwrap(), limits);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, serde::Deserialize)]
+    struct Plain {
+        #[serde(deserialize_with = "super::vec::<3, _, _>")]
+        items: Vec<u8>,
+        #[serde(deserialize_with = "super::string::<5, _>")]
+        name: String,
+        #[serde(deserialize_with = "super::map::<2, _, _, _, _>")]
+        labels: std::collections::BTreeMap<String, u8>,
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn plain_types() -> serde_json::Result<()> {
+        let p: Plain = serde_json::from_str(
+            r#"{"items": [1, 2, 3], "name": "hello", "labels": {"a": 1, "b": 2}}"#,
+        )?;
+        assert_eq!(p.items, [1, 2, 3]);
+        assert_eq!(p.name, "hello");
+        assert_eq!(p.labels.len(), 2);
+
+        let err =
+            serde_json::from_str::<Plain>(r#"{"items": [1, 2, 3, 4], "name": "", "labels": {}}"#)
+                .unwrap_err();
+        assert!(
+            err.to_string().contains("a sequence of at most 3 elements"),
+            "{err}"
+        );
+        let err = serde_json::from_str::<Plain>(r#"{"items": [], "name": "hello!", "labels": {}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid length 6"), "{err}");
+        let err = serde_json::from_str::<Plain>(
+            r#"{"items": [], "name": "", "labels": {"a": 1, "b": 2, "c": 3}}"#,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("invalid length 3, expected a map with at most 2 entries"),
+            "{err}"
+        );
+        Ok(())
+    }
+}
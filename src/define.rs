@@ -0,0 +1,366 @@
+use core::ops::Range;
+
+use crate::{CapError, CapNum};
+
+/// Define a newtype around a capped integer that has the same impls as the capped integer.
+///
+/// The newtype implements [`CapNum`](crate::CapNum), conversions to and from the inner integer,
+/// parsing, formatting and serde support when the `serde` feature is enabled.
+///
+/// The wrapped type must be written as one of `CapU8`, `CapU16`, `CapU32`, `CapU64` or `CapUsize` without a path.
+///
+/// The expansion only contains safe code so it can be used in crates that forbid unsafe code,
+/// the newtype gets its [`CapNum`](crate::CapNum) impl from this crate by delegating to the capped integer.
+///
+/// ```
+/// #![forbid(unsafe_code)]
+///
+/// capped::define_capped_int! {
+///     /// Identifies a shard
+///     pub struct ShardId(CapU16<64>);
+/// }
+///
+/// let id = ShardId::try_from(12).unwrap();
+/// assert_eq!(id.into_inner(), 12);
+/// assert!(ShardId::try_from(64).is_err());
+/// ```
+#[macro_export]
+macro_rules! define_capped_int {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident(CapU8<$n:tt>);) => {
+        $crate::__define_capped_int!($(#[$meta])* $vis $name, $crate::CapU8<$n>, $crate::CapU8Iter<$n>, u8);
+    };
+    ($(#[$meta:meta])* $vis:vis struct $name:ident(CapU16<$n:tt>);) => {
+        $crate::__define_capped_int!($(#[$meta])* $vis $name, $crate::CapU16<$n>, $crate::CapU16Iter<$n>, u16);
+    };
+    ($(#[$meta:meta])* $vis:vis struct $name:ident(CapU32<$n:tt>);) => {
+        $crate::__define_capped_int!($(#[$meta])* $vis $name, $crate::CapU32<$n>, $crate::CapU32Iter<$n>, u32);
+    };
+    ($(#[$meta:meta])* $vis:vis struct $name:ident(CapU64<$n:tt>);) => {
+        $crate::__define_capped_int!($(#[$meta])* $vis $name, $crate::CapU64<$n>, $crate::CapU64Iter<$n>, u64);
+    };
+    ($(#[$meta:meta])* $vis:vis struct $name:ident(CapUsize<$n:tt>);) => {
+        $crate::__define_capped_int!($(#[$meta])* $vis $name, $crate::CapUsize<$n>, $crate::CapUsizeIter<$n>, usize);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_capped_int {
+    ($(#[$meta:meta])* $vis:vis $name:ident, $cap:ty, $iter:ty, $inner:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+        $vis struct $name($cap);
+
+        #[allow(dead_code)]
+        impl $name {
+            /// Create a new value from the capped integer
+            #[must_use]
+            pub const fn new(value: $cap) -> Self {
+                Self(value)
+            }
+
+            /// Create a new value from an integer by modulo the cap.
+            #[must_use]
+            pub const fn new_wrap(value: $inner) -> Self {
+                Self(<$cap>::new_wrap(value))
+            }
+
+            /// Create a new value from an integer by clamping it to the largest value.
+            #[must_use]
+            pub const fn new_saturating(value: $inner) -> Self {
+                Self(<$cap>::new_saturating(value))
+            }
+
+            /// Create a new value from an integer returning [`None`] if it is not in range.
+            #[must_use]
+            pub const fn new_checked(value: $inner) -> Option<Self> {
+                match <$cap>::new_checked(value) {
+                    Some(value) => Some(Self(value)),
+                    None => None,
+                }
+            }
+
+            /// Get the capped integer
+            #[must_use]
+            pub const fn get(self) -> $cap {
+                self.0
+            }
+
+            /// Get the inner value
+            #[must_use]
+            pub const fn into_inner(self) -> $inner {
+                self.0.into_inner()
+            }
+
+            /// Add `rhs` using modulo the cap.
+            #[must_use]
+            pub const fn wrapping_add(self, rhs: $inner) -> Self {
+                Self(self.0.wrapping_add(rhs))
+            }
+
+            /// Takes the current value and increments it by 1 wrapping if it exceeds the cap.
+            #[must_use]
+            pub fn take_increment(&mut self) -> Self {
+                Self(self.0.take_increment())
+            }
+
+            /// Get the next value, returning [`None`] instead of wrapping if this is the last value.
+            #[must_use]
+            pub const fn next(self) -> Option<Self> {
+                match self.0.next() {
+                    Some(value) => Some(Self(value)),
+                    None => None,
+                }
+            }
+
+            /// Get the previous value, returning [`None`] instead of wrapping if this is the first value.
+            #[must_use]
+            pub const fn prev(self) -> Option<Self> {
+                match self.0.prev() {
+                    Some(value) => Some(Self(value)),
+                    None => None,
+                }
+            }
+
+            /// Iterate over every value in ascending order.
+            ///
+            /// This is an [`ExactSizeIterator`] when the capped integer's iterator is.
+            pub fn all() -> ::core::iter::Map<$iter, fn($cap) -> Self> {
+                <$cap>::all().map($name)
+            }
+        }
+
+        impl $crate::__CapNewtype for $name {
+            type Cap = $cap;
+
+            const MIN_VALUE: Self = Self(<$cap as $crate::CapNum>::MIN);
+
+            const MAX_VALUE: Self = Self(<$cap as $crate::CapNum>::MAX);
+
+            fn from_cap(cap: Self::Cap) -> Self {
+                Self(cap)
+            }
+
+            fn into_cap(self) -> Self::Cap {
+                self.0
+            }
+        }
+
+        impl ::core::convert::From<$cap> for $name {
+            fn from(value: $cap) -> Self {
+                Self(value)
+            }
+        }
+
+        impl ::core::convert::From<$name> for $cap {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl ::core::convert::From<$name> for $inner {
+            fn from(value: $name) -> Self {
+                value.0.into_inner()
+            }
+        }
+
+        impl ::core::convert::TryFrom<$inner> for $name {
+            type Error = $crate::CapError<Self>;
+
+            fn try_from(value: $inner) -> Result<Self, Self::Error> {
+                <$cap>::try_from(value)
+                    .map(Self)
+                    .map_err($crate::CapError::cast)
+            }
+        }
+
+        impl ::core::cmp::PartialEq<$inner> for $name {
+            fn eq(&self, other: &$inner) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl ::core::ops::Deref for $name {
+            type Target = $inner;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl ::core::str::FromStr for $name {
+            type Err = $crate::CapParseError<Self>;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse::<$cap>()
+                    .map(Self)
+                    .map_err($crate::CapParseError::cast)
+            }
+        }
+
+        $crate::__define_capped_int_fmt!($name, Display, LowerHex, UpperHex, Octal, Binary);
+        $crate::__define_capped_int_serde!($name, $cap);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_capped_int_fmt {
+    ($name:ident, $($fmt:ident),*) => {
+        $(
+            impl ::core::fmt::$fmt for $name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::$fmt::fmt(&self.0, f)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_capped_int_serde {
+    ($name:ident, $cap:ty) => {
+        impl $crate::__serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::__serde::Serializer,
+            {
+                $crate::__serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> $crate::__serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::__serde::Deserializer<'de>,
+            {
+                <$cap as $crate::__serde::Deserialize<'de>>::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_capped_int_serde {
+    ($name:ident, $cap:ty) => {};
+}
+
+/// A newtype around a capped integer, implemented by [`define_capped_int!`](crate::define_capped_int).
+///
+/// Every type implementing this is a [`CapNum`] that delegates to the wrapped capped integer,
+/// which keeps the `unsafe impl` in this crate so the macro expands to safe code.
+#[doc(hidden)]
+pub trait CapNewtype: Copy + Ord {
+    /// The wrapped capped integer
+    type Cap: CapNum;
+
+    /// The newtype wrapping [`CapNum::MIN`]
+    const MIN_VALUE: Self;
+
+    /// The newtype wrapping [`CapNum::MAX`]
+    const MAX_VALUE: Self;
+
+    /// Wrap the capped integer
+    fn from_cap(cap: Self::Cap) -> Self;
+
+    /// Get the wrapped capped integer
+    fn into_cap(self) -> Self::Cap;
+}
+
+#[allow(unsafe_code)]
+// Safety: every value wraps a `T::Cap` and `into_inner` returns its inner value, which `T::Cap` guarantees is in range
+unsafe impl<T: CapNewtype> CapNum for T {
+    type Inner = <T::Cap as CapNum>::Inner;
+
+    const MIN: Self = T::MIN_VALUE;
+
+    const MAX: Self = T::MAX_VALUE;
+
+    const COUNT: usize = T::Cap::COUNT;
+
+    fn range() -> Range<Self::Inner> {
+        T::Cap::range()
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.into_cap().into_inner()
+    }
+
+    fn try_from_inner(value: Self::Inner) -> Result<Self, CapError<Self>> {
+        T::Cap::try_from_inner(value)
+            .map(T::from_cap)
+            .map_err(CapError::cast)
+    }
+
+    fn new_wrap(value: Self::Inner) -> Self {
+        T::from_cap(T::Cap::new_wrap(value))
+    }
+
+    #[cfg(feature = "serde")]
+    fn __deserialize_inner<'de, D, V>(deserializer: D, visitor: V) -> Result<V::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        V: serde::de::Visitor<'de>,
+    {
+        T::Cap::__deserialize_inner(deserializer, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CapNum, CapU16, CapU8};
+
+    crate::define_capped_int! {
+        /// A shard identifier
+        pub struct ShardId(CapU16<64>);
+    }
+
+    crate::define_capped_int! {
+        struct Hour(CapU8<24>);
+    }
+
+    fn largest<T: CapNum>() -> T {
+        T::MAX
+    }
+
+    #[test]
+    fn newtype() {
+        let id = ShardId::try_from(12).unwrap();
+        assert_eq!(id, 12);
+        assert_eq!(*id, 12);
+        assert_eq!(u16::from(id), 12);
+        assert_eq!(id.get(), CapU16::<64>::new_wrap(12));
+        assert_eq!(ShardId::new_wrap(70), 6);
+        assert_eq!(ShardId::new_saturating(70), 63);
+        assert_eq!(ShardId::new_checked(64), None);
+        assert_eq!(ShardId::MAX.next(), None);
+        assert_eq!(largest::<ShardId>(), 63);
+        assert_eq!(ShardId::COUNT, 64);
+        assert_eq!(ShardId::all().len(), 64);
+        assert_eq!(Hour::new(CapU8::new_wrap(23)).wrapping_add(2), 1);
+    }
+
+    #[test]
+    fn errors() {
+        let err = ShardId::try_from(64).unwrap_err();
+        assert_eq!(err.to_string(), "value 64 is not in range 0..64");
+        let err = "100".parse::<ShardId>().unwrap_err();
+        assert_eq!(err.to_string(), "value 100 is not in range 0..64");
+        assert!("0x".parse::<Hour>().is_err());
+        assert_eq!(format!("{:x}", "31".parse::<ShardId>().unwrap()), "1f");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_newtype() -> serde_json::Result<()> {
+        let ids: Vec<ShardId> = serde_json::from_str("[1, 63]")?;
+        assert_eq!(ids, [ShardId::new_wrap(1), ShardId::new_wrap(63)]);
+        assert_eq!(serde_json::to_string(&ids)?, "[1,63]");
+        assert!(serde_json::from_str::<ShardId>("64").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,725 @@
+//! Capped types whose limit is chosen at runtime, such as from configuration loaded at startup.
+//!
+//! Each value stores its limit. With the `serde` feature they are deserialized through a [`DeserializeSeed`](serde::de::DeserializeSeed)
+//! that takes the limit, see [`DynCapVecSeed`], [`DynCapStringSeed`] and [`DynCapU64Seed`].
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::{CapString, CapStringLengthError, CapVec, CapVecLengthError};
+use crate::{CapU64, ErrorKind};
+
+/// A [`Vec`] with a limit on its length chosen at runtime, its length must be in the range `0..=limit`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DynCapVec<T> {
+    inner: Vec<T>,
+    limit: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> DynCapVec<T> {
+    /// Create an empty [`DynCapVec`] that can hold at most `limit` elements
+    #[must_use]
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            inner: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Create a [`DynCapVec`] from a [`Vec`] if it has at most `limit` elements
+    ///
+    /// # Errors
+    /// Will return `Err` if `vec` is longer than `limit`.
+    pub fn try_new(vec: Vec<T>, limit: usize) -> Result<Self, DynCapLengthError> {
+        if vec.len() <= limit {
+            Ok(Self { inner: vec, limit })
+        } else {
+            Err(DynCapLengthError {
+                len: vec.len(),
+                limit,
+            })
+        }
+    }
+
+    /// Gets the limit on the length
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Gets the length of the vector
+    ///
+    /// See [`Vec::len`]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns [`true`] if the vector contains no elements
+    ///
+    /// See [`Vec::is_empty`]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Pushes an element on to the end of the [`DynCapVec`] if there is space returns [`None`], otherwise returns [`Some`] `element`.
+    ///
+    /// See [`Vec::push`]
+    #[must_use]
+    pub fn push(&mut self, element: T) -> Option<T> {
+        if self.inner.len() < self.limit {
+            self.inner.push(element);
+            None
+        } else {
+            Some(element)
+        }
+    }
+
+    /// Pushes an element on to the end if there is space, otherwise returns `element` as the error.
+    ///
+    /// See [`Vec::push`]
+    /// # Errors
+    /// Will return `Err` with `element` if the vector already holds `limit` elements.
+    pub fn try_push(&mut self, element: T) -> Result<(), T> {
+        self.push(element).map_or(Ok(()), Err)
+    }
+
+    /// Removes the last element and returns it.
+    ///
+    /// See [`Vec::pop`]
+    pub fn pop(&mut self) -> Option<T> {
+        self.inner.pop()
+    }
+
+    /// Clears the [`DynCapVec`] setting the length to `0`.
+    ///
+    /// See [`Vec::clear`]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Get the underlying [`Vec`]
+    #[must_use]
+    pub fn into_inner(self) -> Vec<T> {
+        self.inner
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> AsRef<Vec<T>> for DynCapVec<T> {
+    fn as_ref(&self) -> &Vec<T> {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize, T> From<CapVec<N, T>> for DynCapVec<T> {
+    fn from(value: CapVec<N, T>) -> Self {
+        Self {
+            inner: value.into_inner(),
+            limit: N,
+        }
+    }
+}
+
+/// Succeeds whenever the runtime limit is at most `N`, otherwise only if the current length fits.
+#[cfg(feature = "alloc")]
+impl<const N: usize, T> TryFrom<DynCapVec<T>> for CapVec<N, T> {
+    type Error = CapVecLengthError<N>;
+
+    fn try_from(value: DynCapVec<T>) -> Result<Self, Self::Error> {
+        Self::try_from(value.inner)
+    }
+}
+
+/// A [`String`] with a limit on its length in bytes chosen at runtime, its length must be in the range `0..=limit`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DynCapString {
+    inner: String,
+    limit: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl DynCapString {
+    /// Create an empty [`DynCapString`] that can hold at most `limit` bytes
+    #[must_use]
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            inner: String::new(),
+            limit,
+        }
+    }
+
+    /// Create a [`DynCapString`] from a [`String`] if it is at most `limit` bytes
+    ///
+    /// # Errors
+    /// Will return `Err` if `string` is longer than `limit`.
+    pub fn try_new(string: String, limit: usize) -> Result<Self, DynCapLengthError> {
+        if string.len() <= limit {
+            Ok(Self {
+                inner: string,
+                limit,
+            })
+        } else {
+            Err(DynCapLengthError {
+                len: string.len(),
+                limit,
+            })
+        }
+    }
+
+    /// Gets the limit on the length in bytes
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Extracts a string slice containing the entire string
+    ///
+    /// See [`String::as_str`]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.inner.as_str()
+    }
+
+    /// Appends a given string slice onto the end of this [`DynCapString`] if it will fit within the limit.
+    ///
+    /// See [`String::push_str`]
+    /// # Errors
+    /// Will return `Err` if the new string length would be greater than the limit.
+    pub fn push_str(&mut self, string: &str) -> Result<(), DynCapLengthError> {
+        let len = self.inner.len() + string.len();
+        if len <= self.limit {
+            self.inner.push_str(string);
+            Ok(())
+        } else {
+            Err(DynCapLengthError {
+                len,
+                limit: self.limit,
+            })
+        }
+    }
+
+    /// Returns the inner [`String`]
+    #[must_use]
+    pub fn into_inner(self) -> String {
+        self.inner
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<str> for DynCapString {
+    fn as_ref(&self) -> &str {
+        self.inner.as_ref()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for DynCapString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.inner.as_str())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<CapString<N>> for DynCapString {
+    fn from(value: CapString<N>) -> Self {
+        Self {
+            inner: value.into_inner(),
+            limit: N,
+        }
+    }
+}
+
+/// Succeeds whenever the runtime limit is at most `N`, otherwise only if the current length fits.
+#[cfg(feature = "alloc")]
+impl<const N: usize> TryFrom<DynCapString> for CapString<N> {
+    type Error = CapStringLengthError<N>;
+
+    fn try_from(value: DynCapString) -> Result<Self, Self::Error> {
+        Self::try_from(value.inner)
+    }
+}
+
+/// A [`u64`] capped to a limit chosen at runtime, its value must be in the range `0..cap`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct DynCapU64 {
+    value: u64,
+    cap: u64,
+}
+
+impl DynCapU64 {
+    /// Create a [`DynCapU64`] if `value` is in the range `0..cap`
+    ///
+    /// # Errors
+    /// Will return `Err` if `value` is not less than `cap`.
+    pub const fn new(value: u64, cap: u64) -> Result<Self, DynCapRangeError> {
+        if value < cap {
+            Ok(Self { value, cap })
+        } else {
+            Err(DynCapRangeError { value, cap })
+        }
+    }
+
+    /// Gets the cap, values are always less than it
+    #[must_use]
+    pub const fn cap(self) -> u64 {
+        self.cap
+    }
+
+    /// Get the inner value
+    #[must_use]
+    pub const fn into_inner(self) -> u64 {
+        self.value
+    }
+}
+
+impl core::ops::Deref for DynCapU64 {
+    type Target = u64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl core::fmt::Display for DynCapU64 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.value, f)
+    }
+}
+
+impl<const N: u64> From<CapU64<N>> for DynCapU64 {
+    fn from(value: CapU64<N>) -> Self {
+        Self {
+            value: value.into_inner(),
+            cap: N,
+        }
+    }
+}
+
+/// Succeeds whenever the runtime cap is at most `N`, otherwise only if the current value fits.
+impl<const N: u64> TryFrom<DynCapU64> for CapU64<N> {
+    type Error = DynCapRangeError;
+
+    fn try_from(value: DynCapU64) -> Result<Self, Self::Error> {
+        Self::new_checked(value.value).ok_or(DynCapRangeError {
+            value: value.value,
+            cap: N,
+        })
+    }
+}
+
+/// Error returned when a length is greater than a runtime limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynCapLengthError {
+    len: usize,
+    limit: usize,
+}
+
+impl DynCapLengthError {
+    /// The length that was attempted
+    #[must_use]
+    pub const fn actual(&self) -> usize {
+        self.len
+    }
+
+    /// The limit the length must not exceed
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns [`ErrorKind::TooLong`]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        ErrorKind::TooLong
+    }
+}
+
+impl core::fmt::Display for DynCapLengthError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self { len, limit } = self;
+        f.write_fmt(format_args!("length {len} is longer than {limit}"))
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DynCapLengthError {}
+
+/// Error returned when a value is not less than a runtime cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynCapRangeError {
+    value: u64,
+    cap: u64,
+}
+
+impl DynCapRangeError {
+    /// The value that was attempted
+    #[must_use]
+    pub const fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// The cap the value must be less than
+    #[must_use]
+    pub const fn limit(&self) -> u64 {
+        self.cap
+    }
+
+    /// Returns [`ErrorKind::OutOfRange`]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        ErrorKind::OutOfRange
+    }
+}
+
+impl core::fmt::Display for DynCapRangeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self { value, cap } = self;
+        f.write_fmt(format_args!("value {value} is not in range 0..{cap}"))
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DynCapRangeError {}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    #[cfg(feature = "alloc")]
+    use alloc::string::String;
+    #[cfg(feature = "alloc")]
+    use core::marker::PhantomData;
+
+    #[cfg(not(feature = "alloc"))]
+    use serde::de::Error as _;
+    use serde::de::{DeserializeSeed, Deserializer};
+    #[cfg(feature = "alloc")]
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::Deserialize as _;
+    use serde::{Serialize, Serializer};
+
+    use super::DynCapU64;
+    #[cfg(feature = "alloc")]
+    use super::{DynCapString, DynCapVec};
+
+    #[cfg(feature = "alloc")]
+    impl<T: Serialize> Serialize for DynCapVec<T> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            self.inner.serialize(serializer)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Serialize for DynCapString {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_str(&self.inner)
+        }
+    }
+
+    impl Serialize for DynCapU64 {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_u64(self.value)
+        }
+    }
+
+    /// Deserializes a [`DynCapVec`] with at most `limit` elements
+    ///
+    /// The elements are deserialized with a clone of the seed `S`, which is [`PhantomData`] for any [`Deserialize`](serde::Deserialize) type.
+    /// With another seed the elements can have runtime limits as well:
+    ///
+    /// ```
+    /// use serde::de::DeserializeSeed as _;
+    /// use capped::{DynCapString, DynCapStringSeed, DynCapVecSeed};
+    ///
+    /// let seed = DynCapVecSeed::<DynCapString, _>::with_seed(2, DynCapStringSeed::new(3));
+    /// let mut de = serde_json::Deserializer::from_str(r#"["abc", "de"]"#);
+    /// let names = seed.deserialize(&mut de).unwrap();
+    /// assert_eq!(names.as_ref()[0].as_str(), "abc");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[derive(Debug)]
+    pub struct DynCapVecSeed<T, S = PhantomData<T>> {
+        limit: usize,
+        seed: S,
+        marker: PhantomData<T>,
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<T> DynCapVecSeed<T> {
+        /// Create a seed for a [`DynCapVec`] with at most `limit` elements
+        #[must_use]
+        pub const fn new(limit: usize) -> Self {
+            Self::with_seed(limit, PhantomData)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<T, S> DynCapVecSeed<T, S> {
+        /// Create a seed for a [`DynCapVec`] with at most `limit` elements that are each deserialized with `seed`
+        #[must_use]
+        pub const fn with_seed(limit: usize, seed: S) -> Self {
+            Self {
+                limit,
+                seed,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<T, S: Clone> Clone for DynCapVecSeed<T, S> {
+        fn clone(&self) -> Self {
+            Self::with_seed(self.limit, self.seed.clone())
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<'de, T, S> DeserializeSeed<'de> for DynCapVecSeed<T, S>
+    where
+        S: DeserializeSeed<'de, Value = T> + Clone,
+    {
+        type Value = DynCapVec<T>;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_seq(self)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<'de, T, S> Visitor<'de> for DynCapVecSeed<T, S>
+    where
+        S: DeserializeSeed<'de, Value = T> + Clone,
+    {
+        type Value = DynCapVec<T>;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            let limit = self.limit;
+            formatter.write_fmt(format_args!("a sequence of at most {limit} elements"))
+        }
+
+        fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let inner = crate::vec::visit_capped_seq(seq, self.limit, &self.seed, &self)?;
+            Ok(DynCapVec {
+                inner,
+                limit: self.limit,
+            })
+        }
+    }
+
+    /// Deserializes a [`DynCapString`] with at most `limit` bytes
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, Clone, Copy)]
+    pub struct DynCapStringSeed {
+        limit: usize,
+    }
+
+    #[cfg(feature = "alloc")]
+    impl DynCapStringSeed {
+        /// Create a seed for a [`DynCapString`] with at most `limit` bytes
+        #[must_use]
+        pub const fn new(limit: usize) -> Self {
+            Self { limit }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<'de> DeserializeSeed<'de> for DynCapStringSeed {
+        type Value = DynCapString;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_string(self)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Visitor<'_> for DynCapStringSeed {
+        type Value = DynCapString;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            let limit = self.limit;
+            formatter.write_fmt(format_args!("string with length in the range 0..={limit}"))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            if v.len() <= self.limit {
+                Ok(DynCapString {
+                    inner: String::from(v),
+                    limit: self.limit,
+                })
+            } else {
+                Err(E::invalid_length(v.len(), &self))
+            }
+        }
+
+        fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            if v.len() <= self.limit {
+                Ok(DynCapString {
+                    inner: v,
+                    limit: self.limit,
+                })
+            } else {
+                Err(E::invalid_length(v.len(), &self))
+            }
+        }
+    }
+
+    /// Deserializes a [`DynCapU64`] in the range `0..cap`
+    #[derive(Debug, Clone, Copy)]
+    pub struct DynCapU64Seed {
+        cap: u64,
+    }
+
+    impl DynCapU64Seed {
+        /// Create a seed for a [`DynCapU64`] in the range `0..cap`
+        #[must_use]
+        pub const fn new(cap: u64) -> Self {
+            Self { cap }
+        }
+    }
+
+    impl<'de> DeserializeSeed<'de> for DynCapU64Seed {
+        type Value = DynCapU64;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let value = u64::deserialize(deserializer)?;
+            DynCapU64::new(value, self.cap).map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use serde_impls::DynCapU64Seed;
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub use serde_impls::{DynCapStringSeed, DynCapVecSeed};
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+    use super::{DynCapString, DynCapU64, DynCapVec};
+    use crate::{CapString, CapU64, CapVec};
+
+    #[test]
+    fn dyn_vec() {
+        let mut v = DynCapVec::new(2);
+        assert_eq!(v.push(1), None);
+        assert_eq!(v.push(2), None);
+        assert_eq!(v.push(3), Some(3));
+        assert_eq!(v.try_push(3), Err(3));
+        assert_eq!(v.len(), 2);
+        assert!(CapVec::<1, i32>::try_from(v.clone()).is_err());
+        let cap = CapVec::<2, i32>::try_from(v).unwrap();
+        let back = DynCapVec::from(cap);
+        assert_eq!(back.limit(), 2);
+        let err = DynCapVec::try_new(vec![1, 2, 3], 2).unwrap_err();
+        assert_eq!(err.to_string(), "length 3 is longer than 2");
+    }
+
+    #[test]
+    fn dyn_string() {
+        let mut s = DynCapString::new(5);
+        s.push_str("abc").unwrap();
+        assert!(s.push_str("def").is_err());
+        assert_eq!(s.as_str(), "abc");
+        assert_eq!(CapString::<3>::try_from(s.clone()).unwrap().as_str(), "abc");
+        assert!(CapString::<2>::try_from(s).is_err());
+        assert_eq!(DynCapString::from(CapString::<8>::default()).limit(), 8);
+    }
+
+    #[test]
+    fn dyn_u64() {
+        let v = DynCapU64::new(7, 10).unwrap();
+        assert_eq!(*v, 7);
+        assert_eq!(v.cap(), 10);
+        assert_eq!(CapU64::<8>::try_from(v).unwrap(), 7);
+        let err = CapU64::<5>::try_from(v).unwrap_err();
+        assert_eq!(err.to_string(), "value 7 is not in range 0..5");
+        assert!(DynCapU64::new(10, 10).is_err());
+        assert_eq!(DynCapU64::from(CapU64::<3>::new_wrap(2)).cap(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_seed() -> serde_json::Result<()> {
+        use serde::de::DeserializeSeed as _;
+
+        use super::{DynCapStringSeed, DynCapU64Seed, DynCapVecSeed};
+
+        let limit = 3;
+        let mut de = serde_json::Deserializer::from_str("[1, 2, 3]");
+        let v = DynCapVecSeed::<u8>::new(limit).deserialize(&mut de)?;
+        assert_eq!(v.as_ref(), &[1, 2, 3]);
+        assert_eq!(serde_json::to_string(&v)?, "[1,2,3]");
+        let mut de = serde_json::Deserializer::from_str("[1, 2, 3, 4]");
+        let err = DynCapVecSeed::<u8>::new(limit)
+            .deserialize(&mut de)
+            .unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "invalid length of at least 4, expected a sequence of at most 3 elements"
+            ),
+            "{err}"
+        );
+
+        let names = DynCapVecSeed::<DynCapString, _>::with_seed(2, DynCapStringSeed::new(limit));
+        let mut de = serde_json::Deserializer::from_str(r#"["abc", "d"]"#);
+        let v = names.clone().deserialize(&mut de)?;
+        assert_eq!(v.as_ref()[0].limit(), limit);
+        for json in [r#"["abcd"]"#, r#"["a", "b", "c"]"#] {
+            let mut de = serde_json::Deserializer::from_str(json);
+            assert!(names.clone().deserialize(&mut de).is_err(), "{json}");
+        }
+
+        let mut de = serde_json::Deserializer::from_str(r#""abcd""#);
+        assert!(DynCapStringSeed::new(limit).deserialize(&mut de).is_err());
+        let mut de = serde_json::Deserializer::from_str(r#""abc""#);
+        assert_eq!(
+            DynCapStringSeed::new(limit).deserialize(&mut de)?.as_str(),
+            "abc"
+        );
+
+        let mut de = serde_json::Deserializer::from_str("2");
+        assert_eq!(
+            DynCapU64Seed::new(limit as u64).deserialize(&mut de)?,
+            DynCapU64::new(2, 3).unwrap()
+        );
+        let mut de = serde_json::Deserializer::from_str("3");
+        let err = DynCapU64Seed::new(3).deserialize(&mut de).unwrap_err();
+        assert!(
+            err.to_string().starts_with("value 3 is not in range 0..3"),
+            "{err}"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+use core::num::ParseIntError;
+use core::ops::Bound;
+
+use crate::{
+    CapBytesLengthError, CapConversionError, CapError, CapFloatError, CapNum, CapParseError,
+    DynCapLengthError, DynCapRangeError, FloatRange,
+};
+#[cfg(feature = "alloc")]
+use crate::{CapIdExhaustedError, CapStringLengthError, CapVecLengthError};
+
+/// The kind of limit that was broken, see [`Error::kind`]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A value was outside of its range
+    OutOfRange,
+    /// A length was greater than its cap
+    TooLong,
+    /// A string was not a valid number
+    Invalid,
+    /// Every value was already in use
+    Exhausted,
+}
+
+/// Any error produced by this crate
+///
+/// Every error type in this crate converts into [`Error`] so they can be handled together.
+///
+/// ```
+/// use capped::{CapByteArray, CapU8, Error, ErrorKind};
+///
+/// fn parse(level: &str, name: &str) -> Result<(CapU8<5>, CapByteArray<4>), Error> {
+///     Ok((level.parse()?, CapByteArray::try_from(name.as_bytes())?))
+/// }
+///
+/// let err = parse("3", "hello").unwrap_err();
+/// assert_eq!(err, Error::TooLong { len: 5, limit: 4 });
+/// assert_eq!(parse("9", "").unwrap_err().kind(), ErrorKind::OutOfRange);
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Error {
+    /// An integer was not in the range `0..limit`
+    OutOfRange {
+        /// The value that was rejected
+        value: u128,
+        /// The exclusive upper bound
+        limit: u128,
+    },
+    /// A parsed integer was negative or greater than [`u128::MAX`] so it is not in the range `0..limit`
+    Overflow {
+        /// Whether the integer was negative
+        negative: bool,
+        /// The exclusive upper bound
+        limit: u128,
+    },
+    /// A float was NaN, infinite or not in the range `limit`
+    FloatOutOfRange {
+        /// The value that was rejected
+        value: f64,
+        /// The range the value must be in
+        limit: (Bound<f64>, Bound<f64>),
+    },
+    /// A length was greater than `limit`
+    TooLong {
+        /// The length that was attempted
+        len: usize,
+        /// The inclusive upper bound
+        limit: usize,
+    },
+    /// A string was not a valid number
+    Invalid(ParseIntError),
+    /// All `limit` values were already in use
+    Exhausted {
+        /// The number of values available
+        limit: u128,
+    },
+}
+
+impl Error {
+    /// Returns the kind of limit that was broken
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::OutOfRange { .. } | Self::Overflow { .. } | Self::FloatOutOfRange { .. } => {
+                ErrorKind::OutOfRange
+            }
+            Self::TooLong { .. } => ErrorKind::TooLong,
+            Self::Invalid(_) => ErrorKind::Invalid,
+            Self::Exhausted { .. } => ErrorKind::Exhausted,
+        }
+    }
+}
+
+fn bound_bits(bound: Bound<f64>) -> Bound<u64> {
+    match bound {
+        Bound::Included(v) => Bound::Included(v.to_bits()),
+        Bound::Excluded(v) => Bound::Excluded(v.to_bits()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+// Floats are compared by their bits so a NaN value is equal to itself, which makes `Eq` valid
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::OutOfRange { value, limit },
+                Self::OutOfRange {
+                    value: other_value,
+                    limit: other_limit,
+                },
+            ) => value == other_value && limit == other_limit,
+            (
+                Self::Overflow { negative, limit },
+                Self::Overflow {
+                    negative: other_negative,
+                    limit: other_limit,
+                },
+            ) => negative == other_negative && limit == other_limit,
+            (
+                Self::FloatOutOfRange { value, limit },
+                Self::FloatOutOfRange {
+                    value: other_value,
+                    limit: other_limit,
+                },
+            ) => {
+                value.to_bits() == other_value.to_bits()
+                    && bound_bits(limit.0) == bound_bits(other_limit.0)
+                    && bound_bits(limit.1) == bound_bits(other_limit.1)
+            }
+            (
+                Self::TooLong { len, limit },
+                Self::TooLong {
+                    len: other_len,
+                    limit: other_limit,
+                },
+            ) => len == other_len && limit == other_limit,
+            (Self::Invalid(err), Self::Invalid(other_err)) => err == other_err,
+            (Self::Exhausted { limit }, Self::Exhausted { limit: other_limit }) => {
+                limit == other_limit
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Error {}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OutOfRange { value, limit } => {
+                f.write_fmt(format_args!("value {value} is not in range 0..{limit}"))
+            }
+            Self::Overflow {
+                negative: true,
+                limit,
+            } => f.write_fmt(format_args!("negative value is not in range 0..{limit}")),
+            Self::Overflow {
+                negative: false,
+                limit,
+            } => f.write_fmt(format_args!(
+                "value larger than u128::MAX is not in range 0..{limit}"
+            )),
+            Self::FloatOutOfRange { value, limit } => {
+                f.write_fmt(format_args!("value {value} is not in range "))?;
+                match limit.0 {
+                    Bound::Included(min) | Bound::Excluded(min) => {
+                        core::fmt::Display::fmt(&min, f)?;
+                    }
+                    Bound::Unbounded => {}
+                }
+                match limit.1 {
+                    Bound::Included(max) => f.write_fmt(format_args!("..={max}")),
+                    Bound::Excluded(max) => f.write_fmt(format_args!("..{max}")),
+                    Bound::Unbounded => f.write_str(".."),
+                }
+            }
+            Self::TooLong { len, limit } => {
+                f.write_fmt(format_args!("length {len} is longer than {limit}"))
+            }
+            Self::Invalid(err) => f.write_fmt(format_args!("invalid number: {err}")),
+            Self::Exhausted { limit } => f.write_fmt(format_args!("all {limit} ids are allocated")),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Invalid(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<T: CapNum> From<CapError<T>> for Error {
+    fn from(err: CapError<T>) -> Self {
+        Self::OutOfRange {
+            value: err.value(),
+            limit: err.limit(),
+        }
+    }
+}
+
+impl<T: CapNum> From<CapConversionError<T>> for Error {
+    fn from(err: CapConversionError<T>) -> Self {
+        Self::OutOfRange {
+            value: err.value(),
+            limit: err.limit(),
+        }
+    }
+}
+
+impl<T: CapNum> From<CapParseError<T>> for Error {
+    fn from(err: CapParseError<T>) -> Self {
+        match err {
+            CapParseError::Invalid(err) => Self::Invalid(err),
+            CapParseError::OutOfRange(err) => err.into(),
+            CapParseError::Negative | CapParseError::TooLarge => Self::Overflow {
+                negative: matches!(err, CapParseError::Negative),
+                limit: err.limit(),
+            },
+        }
+    }
+}
+
+impl<R: FloatRange> From<CapFloatError<R>> for Error {
+    fn from(err: CapFloatError<R>) -> Self {
+        Self::FloatOutOfRange {
+            value: err.value(),
+            limit: err.limit(),
+        }
+    }
+}
+
+macro_rules! from_length_error {
+    ($($(#[$attr: meta])* $error: ident),*) => {
+        $(
+            $(#[$attr])*
+            impl<const N: usize> From<$error<N>> for Error {
+                fn from(err: $error<N>) -> Self {
+                    Self::TooLong {
+                        len: err.actual(),
+                        limit: err.limit(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_length_error!(
+    #[cfg(feature = "alloc")]
+    CapVecLengthError,
+    #[cfg(feature = "alloc")]
+    CapStringLengthError,
+    CapBytesLengthError
+);
+
+impl From<DynCapLengthError> for Error {
+    fn from(err: DynCapLengthError) -> Self {
+        Self::TooLong {
+            len: err.actual(),
+            limit: err.limit(),
+        }
+    }
+}
+
+impl From<DynCapRangeError> for Error {
+    fn from(err: DynCapRangeError) -> Self {
+        Self::OutOfRange {
+            value: err.value().into(),
+            limit: err.limit().into(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: u32> From<CapIdExhaustedError<N>> for Error {
+    fn from(err: CapIdExhaustedError<N>) -> Self {
+        Self::Exhausted {
+            limit: err.limit().into(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::ops::Bound;
+
+    use super::{Error, ErrorKind};
+    use crate::{
+        CapF64, CapIdAllocMode, CapIdAllocator, CapString, CapU16, CapU64, CapUsize, CapVec,
+        DynCapString, DynCapU64, Latitude, UnitInterval,
+    };
+
+    #[test]
+    fn convert() {
+        let err = CapU16::<10>::try_from(12).unwrap_err();
+        assert_eq!(
+            (err.value(), err.limit(), err.kind()),
+            (12, 10, ErrorKind::OutOfRange)
+        );
+        assert_eq!(
+            Error::from(err),
+            Error::OutOfRange {
+                value: 12,
+                limit: 10
+            }
+        );
+
+        let err = "70000".parse::<CapU16<10>>().unwrap_err();
+        assert_eq!((err.value(), err.limit()), (Some(70000), 10));
+        assert_eq!(
+            Error::from(err).to_string(),
+            "value 70000 is not in range 0..10"
+        );
+        let err = "x".parse::<CapU16<10>>().unwrap_err();
+        assert_eq!((err.value(), err.kind()), (None, ErrorKind::Invalid));
+        assert!(std::error::Error::source(&Error::from(err)).is_some());
+        let err = Error::from("-3".parse::<CapU16<10>>().unwrap_err());
+        assert_eq!(
+            err,
+            Error::Overflow {
+                negative: true,
+                limit: 10
+            }
+        );
+        assert_eq!(err.to_string(), "negative value is not in range 0..10");
+        assert_eq!(err.kind(), ErrorKind::OutOfRange);
+
+        let err = CapUsize::<4>::new_wrap(3).try_recap::<2>().unwrap_err();
+        assert_eq!(Error::from(err), Error::OutOfRange { value: 3, limit: 2 });
+        let err = CapU64::<5>::try_from(u64::MAX).unwrap_err();
+        assert_eq!(err.value(), u128::from(u64::MAX));
+
+        let err = CapVec::<2, u8>::try_from(vec![1, 2, 3]).unwrap_err();
+        assert_eq!(
+            (err.actual(), err.limit(), err.kind()),
+            (3, 2, ErrorKind::TooLong)
+        );
+        assert_eq!(Error::from(err), Error::TooLong { len: 3, limit: 2 });
+        let err = CapString::<2>::try_from(String::from("abc")).unwrap_err();
+        assert_eq!(err.clone(), err);
+        assert_eq!(Error::from(err).to_string(), "length 3 is longer than 2");
+        let err = DynCapString::try_new(String::from("ab"), 1).unwrap_err();
+        assert_eq!(Error::from(err), Error::TooLong { len: 2, limit: 1 });
+        let err = DynCapU64::new(7, 5).unwrap_err();
+        assert_eq!((err.value(), err.limit()), (7, 5));
+        assert_eq!(Error::from(err), Error::OutOfRange { value: 7, limit: 5 });
+
+        let err = CapF64::<UnitInterval>::try_from(f64::NAN).unwrap_err();
+        assert_eq!(err, err);
+        let err = Error::from(err);
+        assert_eq!(err, err.clone());
+        assert_eq!(err.to_string(), "value NaN is not in range 0..=1");
+        let err = CapF64::<Latitude>::try_from(100.0).unwrap_err();
+        assert_eq!(err.limit(), (Bound::Included(-90.0), Bound::Included(90.0)));
+        assert_eq!(Error::from(err).kind(), ErrorKind::OutOfRange);
+
+        let mut ids = CapIdAllocator::<1>::new(CapIdAllocMode::default());
+        ids.allocate().unwrap();
+        let err = ids.allocate().unwrap_err();
+        assert_eq!((err.limit(), err.kind()), (1, ErrorKind::Exhausted));
+        assert_eq!(Error::from(err).to_string(), "all 1 ids are allocated");
+    }
+}
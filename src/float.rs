@@ -0,0 +1,424 @@
+use core::marker::PhantomData;
+use core::ops::Bound;
+
+use crate::ErrorKind;
+
+/// The range of values a capped float can hold.
+///
+/// Stable const generics can't take floats so the bounds are given by implementing this trait on a marker type.
+/// The range is `MIN..=MAX` when [`FloatRange::MAX_INCLUSIVE`] is `true` and `MIN..MAX` otherwise.
+///
+/// Both bounds must be finite and `MIN` must be less than `MAX`, this is checked at compile time.
+///
+/// ```compile_fail
+/// use capped::{CapF64, FloatRange};
+///
+/// struct Backwards;
+///
+/// impl FloatRange for Backwards {
+///     const MIN: f64 = 1.0;
+///     const MAX: f64 = 0.0;
+/// }
+///
+/// let _ = CapF64::<Backwards>::try_from(0.5);
+/// ```
+pub trait FloatRange {
+    /// The smallest value in the range, this is always included
+    const MIN: f64;
+    /// The upper bound of the range
+    const MAX: f64;
+    /// Whether `MAX` is included in the range
+    const MAX_INCLUSIVE: bool = true;
+
+    /// Returns [`true`] if `value` is finite and within the range
+    #[must_use]
+    fn contains(value: f64) -> bool {
+        value.is_finite()
+            && value >= Self::MIN
+            && (value < Self::MAX || (Self::MAX_INCLUSIVE && value <= Self::MAX))
+    }
+}
+
+/// The range `0.0..=1.0`, useful for probabilities and ratios
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnitInterval;
+
+impl FloatRange for UnitInterval {
+    const MIN: f64 = 0.0;
+    const MAX: f64 = 1.0;
+}
+
+/// The range `-90.0..=90.0` of latitudes in degrees
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Latitude;
+
+impl FloatRange for Latitude {
+    const MIN: f64 = -90.0;
+    const MAX: f64 = 90.0;
+}
+
+/// The range `-180.0..180.0` of longitudes in degrees
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Longitude;
+
+impl FloatRange for Longitude {
+    const MIN: f64 = -180.0;
+    const MAX: f64 = 180.0;
+    const MAX_INCLUSIVE: bool = false;
+}
+
+/// Error produced when a float is NaN, infinite or outside of the range R
+#[derive(Debug, Clone, Copy)]
+pub struct CapFloatError<R> {
+    value: f64,
+    marker: PhantomData<R>,
+}
+
+impl<R> CapFloatError<R> {
+    const fn new(value: f64) -> Self {
+        Self {
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// The value that was rejected
+    #[must_use]
+    pub const fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Returns [`ErrorKind::OutOfRange`]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        ErrorKind::OutOfRange
+    }
+}
+
+impl<R: FloatRange> CapFloatError<R> {
+    /// The range of R the value must be in
+    #[must_use]
+    pub const fn limit(&self) -> (Bound<f64>, Bound<f64>) {
+        let max = if R::MAX_INCLUSIVE {
+            Bound::Included(R::MAX)
+        } else {
+            Bound::Excluded(R::MAX)
+        };
+        (Bound::Included(R::MIN), max)
+    }
+}
+
+// Compared by bits so a NaN value is equal to itself, which makes `Eq` valid
+impl<R> PartialEq for CapFloatError<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value.to_bits() == other.value.to_bits()
+    }
+}
+
+impl<R> Eq for CapFloatError<R> {}
+
+impl<R: FloatRange> core::fmt::Display for CapFloatError<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let value = self.value;
+        let min = R::MIN;
+        let max = R::MAX;
+        let op = if R::MAX_INCLUSIVE { "..=" } else { ".." };
+        f.write_fmt(format_args!("value {value} is not in range {min}{op}{max}"))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: FloatRange + core::fmt::Debug> std::error::Error for CapFloatError<R> {}
+
+macro_rules! float {
+    ($cap_name:ident, $inner:ident, $str_inner:literal) => {
+        #[doc = concat!("A finite [`", $str_inner, "`] capped to the range given by `R`.")]
+        ///
+        /// NaN and infinities are always rejected, which makes [`Eq`], [`Ord`] and [`Hash`] valid.
+        /// Negative zero is stored as positive zero.
+        pub struct $cap_name<R>($inner, PhantomData<R>);
+
+        impl<R: FloatRange> $cap_name<R> {
+            // Written without `is_finite` which is not const, comparisons with NaN are always false
+            const VALID: () = assert!(
+                R::MIN > f64::NEG_INFINITY && R::MAX < f64::INFINITY && R::MIN < R::MAX,
+                "the bounds of R must be finite and MIN must be less than MAX"
+            );
+
+            #[doc = concat!("Create a new [`Self`] from a [`", $str_inner, "`] by clamping it into the range.")]
+            ///
+            /// Infinities are clamped to the nearest bound.
+            #[doc = concat!("Returns [`None`] if `value` is NaN or if no [`", $str_inner, "`] is in the range.")]
+            #[must_use]
+            #[allow(clippy::cast_possible_truncation)]
+            pub fn new_clamp(value: $inner) -> Option<Self> {
+                let () = Self::VALID;
+                if value.is_nan() {
+                    return None;
+                }
+                let mut clamped = value.clamp(R::MIN as $inner, R::MAX as $inner);
+                // The bounds may round outside the range when converted, or the upper bound may be excluded.
+                // One step fixes either, if both are needed the range is too narrow to hold any value.
+                if f64::from(clamped) < R::MIN {
+                    clamped = step(clamped, true);
+                }
+                if !R::contains(f64::from(clamped)) {
+                    clamped = step(clamped, false);
+                }
+                if R::contains(f64::from(clamped)) {
+                    Some(Self::new_unchecked(clamped))
+                } else {
+                    None
+                }
+            }
+
+            const fn new_unchecked(value: $inner) -> Self {
+                let () = Self::VALID;
+                // Normalize negative zero so that equal values have equal bits
+                if value == 0.0 {
+                    Self(0.0, PhantomData)
+                } else {
+                    Self(value, PhantomData)
+                }
+            }
+
+            /// Get the inner value
+            #[must_use]
+            pub const fn into_inner(self) -> $inner {
+                self.0
+            }
+        }
+
+        /// Returns the next representable float above or below `value`
+        fn step(value: $inner, up: bool) -> $inner {
+            if value == 0.0 {
+                let tiny = <$inner>::from_bits(1);
+                return if up { tiny } else { -tiny };
+            }
+            let bits = value.to_bits();
+            if (value > 0.0) == up {
+                <$inner>::from_bits(bits + 1)
+            } else {
+                <$inner>::from_bits(bits - 1)
+            }
+        }
+
+        impl<R: FloatRange> TryFrom<$inner> for $cap_name<R> {
+            type Error = CapFloatError<R>;
+
+            fn try_from(value: $inner) -> Result<Self, Self::Error> {
+                if R::contains(f64::from(value)) {
+                    Ok(Self::new_unchecked(value))
+                } else {
+                    Err(CapFloatError::new(f64::from(value)))
+                }
+            }
+        }
+
+        impl<R> From<$cap_name<R>> for $inner {
+            fn from(value: $cap_name<R>) -> Self {
+                value.0
+            }
+        }
+
+        impl<R> core::ops::Deref for $cap_name<R> {
+            type Target = $inner;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<R> core::fmt::Debug for $cap_name<R> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_tuple(stringify!($cap_name)).field(&self.0).finish()
+            }
+        }
+
+        impl<R> core::fmt::Display for $cap_name<R> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl<R> Clone for $cap_name<R> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<R> Copy for $cap_name<R> {}
+
+        impl<R> PartialEq for $cap_name<R> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl<R> Eq for $cap_name<R> {}
+
+        impl<R> PartialOrd for $cap_name<R> {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl<R> Ord for $cap_name<R> {
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                // Neither value can be NaN so they are always comparable
+                self.0
+                    .partial_cmp(&other.0)
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            }
+        }
+
+        impl<R> core::hash::Hash for $cap_name<R> {
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                self.0.to_bits().hash(state);
+            }
+        }
+
+        impl<R> PartialEq<$inner> for $cap_name<R> {
+            fn eq(&self, other: &$inner) -> bool {
+                self.0 == *other
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<R> serde::Serialize for $cap_name<R> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de, R: FloatRange> serde::Deserialize<'de> for $cap_name<R> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value = <$inner as serde::Deserialize>::deserialize(deserializer)?;
+                Self::try_from(value).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+pub mod cap_f32 {
+    use core::marker::PhantomData;
+
+    use super::{CapFloatError, FloatRange};
+
+    float!(CapF32, f32, "f32");
+}
+
+pub mod cap_f64 {
+    use core::marker::PhantomData;
+
+    use super::{CapFloatError, FloatRange};
+
+    float!(CapF64, f64, "f64");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cap_f32::CapF32;
+    use super::cap_f64::CapF64;
+    use super::{FloatRange, Latitude, Longitude, UnitInterval};
+
+    #[derive(Debug)]
+    struct Tenth;
+
+    impl FloatRange for Tenth {
+        const MIN: f64 = -0.1;
+        const MAX: f64 = 0.1;
+        const MAX_INCLUSIVE: bool = false;
+    }
+
+    /// A range between two neighbouring `f32` values
+    #[derive(Debug)]
+    struct Narrow;
+
+    impl FloatRange for Narrow {
+        const MIN: f64 = 1.000_000_01;
+        const MAX: f64 = 1.000_000_02;
+    }
+
+    #[test]
+    fn try_from() {
+        assert_eq!(CapF64::<UnitInterval>::try_from(0.5).unwrap(), 0.5);
+        assert_eq!(CapF64::<UnitInterval>::try_from(1.0).unwrap(), 1.0);
+        assert!(CapF64::<UnitInterval>::try_from(1.01).is_err());
+        assert!(CapF64::<UnitInterval>::try_from(f64::NAN).is_err());
+        assert!(CapF64::<Latitude>::try_from(f64::INFINITY).is_err());
+        assert!(CapF32::<Longitude>::try_from(180.0).is_err());
+        assert!(CapF32::<Longitude>::try_from(-180.0).is_ok());
+        let err = CapF64::<UnitInterval>::try_from(-2.5).unwrap_err();
+        assert_eq!(err.value().to_bits(), (-2.5f64).to_bits());
+        assert_eq!(err.to_string(), "value -2.5 is not in range 0..=1");
+        assert_eq!(
+            CapF64::<Longitude>::try_from(200.0)
+                .unwrap_err()
+                .to_string(),
+            "value 200 is not in range -180..180"
+        );
+    }
+
+    #[test]
+    fn clamp() {
+        assert_eq!(CapF64::<UnitInterval>::new_clamp(3.0).unwrap(), 1.0);
+        assert_eq!(CapF64::<UnitInterval>::new_clamp(-3.0).unwrap(), 0.0);
+        assert_eq!(
+            CapF64::<UnitInterval>::new_clamp(f64::NEG_INFINITY).unwrap(),
+            0.0
+        );
+        assert!(CapF64::<UnitInterval>::new_clamp(f64::NAN).is_none());
+
+        let lon = CapF64::<Longitude>::new_clamp(500.0).unwrap();
+        assert!(*lon < 180.0 && *lon > 179.99);
+
+        // 0.1 rounds up when converted to f32 so it must be stepped back into range
+        let tenth = CapF32::<Tenth>::new_clamp(1.0).unwrap();
+        assert!(f64::from(*tenth) < 0.1);
+        let tenth = CapF32::<Tenth>::new_clamp(-1.0).unwrap();
+        assert!(f64::from(*tenth) >= -0.1);
+
+        // No f32 is in the range so there is nothing to clamp to
+        assert!(CapF32::<Narrow>::new_clamp(1.0).is_none());
+        assert!(CapF32::<Narrow>::new_clamp(2.0).is_none());
+        assert!(CapF64::<Narrow>::new_clamp(2.0).is_some());
+    }
+
+    #[test]
+    fn ordering() {
+        let mut values: Vec<CapF64<Latitude>> = [45.0, -10.0, 0.0, -0.0, 90.0]
+            .into_iter()
+            .map(|v| CapF64::try_from(v).unwrap())
+            .collect();
+        values.sort();
+        values.dedup();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[0], -10.0);
+        assert_eq!(values[3], 90.0);
+        assert_eq!(
+            CapF64::<Latitude>::try_from(-0.0)
+                .unwrap()
+                .into_inner()
+                .to_bits(),
+            0.0f64.to_bits()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_float() -> serde_json::Result<()> {
+        let v: CapF64<UnitInterval> = serde_json::from_str("0.25")?;
+        assert_eq!(v, 0.25);
+        assert_eq!(serde_json::to_string(&v)?, "0.25");
+        assert!(serde_json::from_str::<CapF32<UnitInterval>>("1.5").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+//! Serialize [`CapBytes`](crate::CapBytes) and [`CapByteArray`](crate::CapByteArray) as hex strings for use with `#[serde(with = "capped::hex")]`
+//!
+//! Human readable formats store lowercase hex instead of the default base64, binary formats still store plain bytes.
+//! Deserializing accepts both lowercase and uppercase hex.
+
+use serde::{Deserializer, Serializer};
+
+use crate::bytes::Encoding;
+use crate::CapByteBuf;
+
+/// Serialize bytes as a hex string
+///
+/// # Errors
+/// Will return `Err` if the serializer fails.
+pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: CapByteBuf,
+{
+    crate::bytes::serialize(value.as_ref(), Encoding::Hex, serializer)
+}
+
+/// Deserialize bytes from a hex string
+///
+/// # Errors
+/// Will return `Err` if the input is not valid hex or is longer than the cap.
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: CapByteBuf,
+{
+    crate::bytes::deserialize(deserializer, Encoding::Hex)
+}
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+    use crate::{CapByteArray, CapBytes};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Key {
+        #[serde(with = "super")]
+        id: CapByteArray<4>,
+        #[serde(with = "super")]
+        data: CapBytes<8>,
+    }
+
+    #[test]
+    fn hex() -> serde_json::Result<()> {
+        let key = Key {
+            id: CapByteArray::try_from(&[0xde, 0xad, 0x0b][..]).unwrap(),
+            data: CapBytes::new(),
+        };
+        let json = serde_json::to_string(&key)?;
+        assert_eq!(json, r#"{"id":"dead0b","data":""}"#);
+        assert_eq!(serde_json::from_str::<Key>(&json)?, key);
+        assert_eq!(
+            serde_json::from_str::<Key>(r#"{"id":"DEAD0B","data":""}"#)?,
+            key
+        );
+
+        let err = serde_json::from_str::<Key>(r#"{"id":"0011223344","data":""}"#).unwrap_err();
+        assert!(err.to_string().starts_with("invalid length 5"), "{err}");
+        for invalid in ["abc", "zz", "+1"] {
+            let json = format!(r#"{{"id":"{invalid}","data":""}}"#);
+            assert!(serde_json::from_str::<Key>(&json).is_err(), "{invalid}");
+        }
+
+        // Binary formats are unaffected
+        let bytes = rmp_serde::to_vec(&key).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Key>(&bytes).unwrap(), key);
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{CapU32, ErrorKind};
+
+const BITS: usize = u64::BITS as usize;
+
+/// How a [`CapIdAllocator`] picks the next id
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum CapIdAllocMode {
+    /// Hand out ids in increasing order wrapping back to `0` at the cap, skipping ids that are still allocated.
+    ///
+    /// This delays reusing a freed id for as long as possible.
+    #[default]
+    Sequential,
+    /// Hand out the lowest id that is not allocated.
+    LowestFree,
+}
+
+/// Allocates [`CapU32<N>`] ids, skipping ids that are still in use.
+///
+/// The allocated ids are tracked with one bit per id, and every word of 64 bits has a bit a level up that is set when the word is full.
+/// Finding a free id looks at one word per level and there are at most 6 levels for any `N`,
+/// so allocating and freeing take constant time however many ids are in use.
+///
+/// ```
+/// use capped::{CapIdAllocMode, CapIdAllocator};
+///
+/// let mut ids = CapIdAllocator::<3>::new(CapIdAllocMode::Sequential);
+/// let a = ids.allocate().unwrap();
+/// let b = ids.allocate().unwrap();
+/// let c = ids.allocate().unwrap();
+/// assert!(ids.allocate().is_err());
+/// ids.free(b);
+/// assert_eq!(ids.allocate(), Ok(b));
+/// # let _ = (a, c);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapIdAllocator<const N: u32> {
+    used: UsedIds,
+    len: u32,
+    next: u32,
+    mode: CapIdAllocMode,
+}
+
+impl<const N: u32> CapIdAllocator<N> {
+    /// Create an allocator with no ids allocated
+    #[must_use]
+    pub fn new(mode: CapIdAllocMode) -> Self {
+        Self {
+            used: UsedIds::new(N as usize),
+            len: 0,
+            next: 0,
+            mode,
+        }
+    }
+
+    /// Returns how the allocator picks the next id
+    #[must_use]
+    pub const fn mode(&self) -> CapIdAllocMode {
+        self.mode
+    }
+
+    /// Allocate an id that is not in use
+    ///
+    /// # Errors
+    /// Will return `Err` if all `N` ids are allocated.
+    pub fn allocate(&mut self) -> Result<CapU32<N>, CapIdExhaustedError<N>> {
+        let start = match self.mode {
+            CapIdAllocMode::Sequential => self.next as usize,
+            CapIdAllocMode::LowestFree => 0,
+        };
+        let id = self
+            .used
+            .first_free_from(start)
+            .or_else(|| self.used.first_free_from(0))
+            .and_then(|index| u32::try_from(index).ok())
+            .and_then(CapU32::new_checked)
+            .ok_or(CapIdExhaustedError)?;
+        self.used.set(id.into_inner() as usize, true);
+        self.len += 1;
+        self.next = id.wrapping_add(1).into_inner();
+        Ok(id)
+    }
+
+    /// Free an id so it can be allocated again, returns whether the id was allocated.
+    pub fn free(&mut self, id: CapU32<N>) -> bool {
+        let freed = self.is_allocated(id);
+        if freed {
+            self.used.set(id.into_inner() as usize, false);
+            self.len -= 1;
+        }
+        freed
+    }
+
+    /// Returns [`true`] if the id is allocated
+    #[must_use]
+    pub fn is_allocated(&self, id: CapU32<N>) -> bool {
+        self.used.contains(id.into_inner() as usize)
+    }
+
+    /// Returns the number of allocated ids
+    #[must_use]
+    pub const fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns [`true`] if no ids are allocated
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns [`true`] if every id is allocated
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Free every id
+    pub fn clear(&mut self) {
+        self.used = UsedIds::new(N as usize);
+        self.len = 0;
+        self.next = 0;
+    }
+}
+
+/// A bit per id that is set when the id is allocated, with a level above for every 64 words where a bit is set when its word is full.
+///
+/// Bits past the last id are set so they are never free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UsedIds {
+    /// The first level has a bit per id and the last level is a single word
+    levels: Box<[Box<[u64]>]>,
+}
+
+impl UsedIds {
+    fn new(len: usize) -> Self {
+        let mut levels = Vec::new();
+        let mut level = Self::padded(len);
+        while level.len() > 1 {
+            let mut above = Self::padded(level.len());
+            for (i, word) in level.iter().enumerate() {
+                if *word == u64::MAX {
+                    above[i / BITS] |= 1 << (i % BITS);
+                }
+            }
+            levels.push(level);
+            level = above;
+        }
+        levels.push(level);
+        Self {
+            levels: levels.into_boxed_slice(),
+        }
+    }
+
+    /// Words for `bits` bits with the bits past the end set
+    fn padded(bits: usize) -> Box<[u64]> {
+        let words = ((bits + BITS - 1) / BITS).max(1);
+        let mut level = vec![0; words].into_boxed_slice();
+        let used = bits - (words - 1) * BITS;
+        if used < BITS {
+            level[words - 1] |= u64::MAX << used;
+        }
+        level
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.levels[0][index / BITS] & 1 << (index % BITS) != 0
+    }
+
+    fn set(&mut self, index: usize, used: bool) {
+        let mut index = index;
+        for level in self.levels.iter_mut() {
+            let word = &mut level[index / BITS];
+            let was_full = *word == u64::MAX;
+            if used {
+                *word |= 1 << (index % BITS);
+            } else {
+                *word &= !(1 << (index % BITS));
+            }
+            // The level above only changes when the word fills up or stops being full
+            if (*word == u64::MAX) == was_full {
+                break;
+            }
+            index /= BITS;
+        }
+    }
+
+    /// Returns the smallest free index that is at least `start`
+    fn first_free_from(&self, start: usize) -> Option<usize> {
+        // Go up until a word has a free bit at or after the position
+        let mut level = 0;
+        let mut index = start;
+        loop {
+            let word = *self.levels[level].get(index / BITS)?;
+            let free = !word & u64::MAX << (index % BITS);
+            if free != 0 {
+                index = index / BITS * BITS + free.trailing_zeros() as usize;
+                break;
+            }
+            level += 1;
+            if level == self.levels.len() {
+                return None;
+            }
+            // The bit for the next word
+            index = index / BITS + 1;
+        }
+        // Go down taking the first word that is not full
+        while level > 0 {
+            level -= 1;
+            let word = self.levels[level][index];
+            index = index * BITS + (!word).trailing_zeros() as usize;
+        }
+        Some(index)
+    }
+}
+
+/// Error returned when every id of a [`CapIdAllocator`] is allocated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapIdExhaustedError<const N: u32>;
+
+impl<const N: u32> CapIdExhaustedError<N> {
+    /// The number of ids, which are all allocated
+    #[must_use]
+    pub const fn limit(&self) -> u32 {
+        N
+    }
+
+    /// The number of ids that are allocated, always `N`
+    #[must_use]
+    pub const fn actual(&self) -> u32 {
+        N
+    }
+
+    /// Returns [`ErrorKind::Exhausted`]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        ErrorKind::Exhausted
+    }
+}
+
+impl<const N: u32> core::fmt::Display for CapIdExhaustedError<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_fmt(format_args!("all {N} ids are allocated"))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: u32> std::error::Error for CapIdExhaustedError<N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{CapIdAllocMode, CapIdAllocator};
+    use crate::CapU32;
+
+    fn id<const N: u32>(i: u32) -> CapU32<N> {
+        CapU32::new_checked(i).unwrap()
+    }
+
+    #[test]
+    fn sequential() {
+        let mut ids = CapIdAllocator::<100>::new(CapIdAllocMode::Sequential);
+        for i in 0..100 {
+            assert_eq!(ids.allocate(), Ok(id(i)));
+        }
+        assert!(ids.is_full());
+        let err = ids.allocate().unwrap_err();
+        assert_eq!(err.to_string(), "all 100 ids are allocated");
+
+        assert!(ids.free(id(70)));
+        assert!(!ids.free(id(70)));
+        assert!(ids.free(id(5)));
+        assert_eq!(ids.len(), 98);
+        // Wraps around and skips ids that are still allocated
+        assert_eq!(ids.allocate(), Ok(id(5)));
+        assert_eq!(ids.allocate(), Ok(id(70)));
+        ids.free(id(3));
+        ids.free(id(90));
+        assert_eq!(ids.allocate(), Ok(id(90)));
+        assert_eq!(ids.allocate(), Ok(id(3)));
+    }
+
+    #[test]
+    fn lowest_free() {
+        let mut ids = CapIdAllocator::<130>::new(CapIdAllocMode::LowestFree);
+        for i in 0..130 {
+            assert_eq!(ids.allocate(), Ok(id(i)));
+        }
+        ids.free(id(128));
+        ids.free(id(64));
+        ids.free(id(2));
+        assert_eq!(ids.allocate(), Ok(id(2)));
+        assert_eq!(ids.allocate(), Ok(id(64)));
+        assert_eq!(ids.allocate(), Ok(id(128)));
+        assert!(ids.allocate().is_err());
+        ids.clear();
+        assert!(ids.is_empty());
+        assert_eq!(ids.allocate(), Ok(id(0)));
+    }
+
+    #[test]
+    fn many_levels() {
+        // 5000 ids take 79 words, which have 2 words above them and then a single word
+        let mut ids = CapIdAllocator::<5000>::new(CapIdAllocMode::LowestFree);
+        for i in 0..5000 {
+            assert_eq!(ids.allocate(), Ok(id(i)));
+        }
+        assert!(ids.allocate().is_err());
+        ids.free(id(4999));
+        ids.free(id(4096));
+        ids.free(id(63));
+        assert_eq!(ids.allocate(), Ok(id(63)));
+        assert_eq!(ids.allocate(), Ok(id(4096)));
+        assert_eq!(ids.allocate(), Ok(id(4999)));
+        assert!(ids.is_full());
+
+        let mut ids = CapIdAllocator::<5000>::new(CapIdAllocMode::Sequential);
+        for i in 0..4500 {
+            assert_eq!(ids.allocate(), Ok(id(i)));
+        }
+        ids.free(id(10));
+        for i in 4500..5000 {
+            assert_eq!(ids.allocate(), Ok(id(i)));
+        }
+        assert_eq!(ids.allocate(), Ok(id(10)));
+        assert!(ids.allocate().is_err());
+    }
+
+    #[test]
+    fn whole_words() {
+        let mut ids = CapIdAllocator::<64>::new(CapIdAllocMode::LowestFree);
+        for i in 0..64 {
+            assert_eq!(ids.allocate(), Ok(id(i)));
+        }
+        assert!(ids.allocate().is_err());
+        let mut ids = CapIdAllocator::<4096>::new(CapIdAllocMode::Sequential);
+        for i in 0..4096 {
+            assert_eq!(ids.allocate(), Ok(id(i)));
+        }
+        assert!(ids.allocate().is_err());
+    }
+
+    #[test]
+    fn empty() {
+        let mut ids = CapIdAllocator::<0>::new(CapIdAllocMode::default());
+        assert!(ids.is_full());
+        assert!(ids.allocate().is_err());
+    }
+}
@@ -1,16 +1,101 @@
 //! Capped is a series of wrappers around types in the core and std library that have a maximum length.
 //!
 //! This is useful for dealing with deserializing and serde support can be enabled with the `serde` feature flag.
+//! Random generation of capped values can be enabled with the `rand` feature flag, see [`random`].
+//! JSON Schemas that describe the caps can be generated with [`schemars`](https://docs.rs/schemars) by enabling the `schemars` feature flag,
+//! and schemas for API documentation with [`utoipa`](https://docs.rs/utoipa) by enabling the `utoipa` feature flag.
+//!
+//! The crate is `no_std` when the default `std` feature flag is disabled.
+//! The `alloc` feature flag enables the types that allocate such as [`CapVec`] and [`CapString`],
+//! while the inline types such as the capped integers, [`CapArray`], [`CapBitSet`] and [`CapByteArray`] are always available.
+//! Serde support works without either.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod array;
+pub mod array_map;
+#[cfg(feature = "serde")]
+pub mod as_string;
+mod atomic;
+mod bitset;
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub mod budget;
+mod bytes;
+#[cfg(feature = "serde")]
+pub mod de;
+mod define;
+mod dynamic;
+mod error;
+mod float;
+#[cfg(feature = "serde")]
+pub mod hex;
+#[cfg(feature = "alloc")]
+mod id_allocator;
 mod num;
+#[cfg(feature = "utoipa")]
+mod openapi;
+#[cfg(feature = "rand")]
+pub mod random;
+#[cfg(feature = "schemars")]
+mod schema;
+mod serial;
+#[cfg(feature = "alloc")]
 mod string;
+#[cfg(all(feature = "serde", feature = "std"))]
+pub mod truncate;
+#[cfg(feature = "alloc")]
 mod vec;
+#[cfg(all(feature = "serde_with", feature = "alloc"))]
+pub mod with;
 
-pub use num::cap_u16::CapU16;
-pub use num::cap_u32::CapU32;
-pub use num::cap_u64::CapU64;
-pub use num::cap_u8::CapU8;
-pub use num::cap_usize::CapUsize;
-pub use num::CapError;
+pub use array::CapArray;
+pub use array_map::{CapArrayMap, CapArrayMapEntry};
+#[cfg(target_has_atomic = "16")]
+pub use atomic::AtomicCapU16;
+#[cfg(target_has_atomic = "32")]
+pub use atomic::AtomicCapU32;
+#[cfg(target_has_atomic = "64")]
+pub use atomic::AtomicCapU64;
+#[cfg(target_has_atomic = "8")]
+pub use atomic::AtomicCapU8;
+#[cfg(target_has_atomic = "ptr")]
+pub use atomic::AtomicCapUsize;
+pub use bitset::{CapBitSet, CapBitSetIter};
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub use budget::CapDepth;
+#[cfg(feature = "alloc")]
+pub use bytes::CapBytes;
+pub use bytes::{CapByteArray, CapByteBuf, CapBytesLengthError};
+#[cfg(feature = "serde")]
+pub use dynamic::DynCapU64Seed;
+pub use dynamic::{DynCapLengthError, DynCapRangeError, DynCapU64};
+#[cfg(feature = "alloc")]
+pub use dynamic::{DynCapString, DynCapVec};
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub use dynamic::{DynCapStringSeed, DynCapVecSeed};
+pub use error::{Error, ErrorKind};
+pub use float::cap_f32::CapF32;
+pub use float::cap_f64::CapF64;
+pub use float::{CapFloatError, FloatRange, Latitude, Longitude, UnitInterval};
+#[cfg(feature = "alloc")]
+pub use id_allocator::{CapIdAllocMode, CapIdAllocator, CapIdExhaustedError};
+pub use num::cap_u16::{CapU16, CapU16Iter};
+pub use num::cap_u32::{CapU32, CapU32Iter};
+pub use num::cap_u64::{CapU64, CapU64Iter};
+pub use num::cap_u8::{CapU8, CapU8Iter};
+pub use num::cap_usize::{CapUsize, CapUsizeIter};
+pub use num::{CapConversionError, CapError, CapNum, CapParseError};
+pub use serial::{SerialCapU16, SerialCapU32, SerialCapU64, SerialCapU8, SerialCapUsize};
+#[cfg(feature = "alloc")]
 pub use string::{CapString, CapStringLengthError};
+#[cfg(feature = "alloc")]
 pub use vec::{CapVec, CapVecLengthError};
+
+#[doc(hidden)]
+pub use define::CapNewtype as __CapNewtype;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde as __serde;
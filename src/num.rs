@@ -1,9 +1,52 @@
 use core::marker::PhantomData;
+use core::num::{IntErrorKind, ParseIntError};
 use core::ops::Range;
 
+use crate::ErrorKind;
+
 /// Error produced when a cap is exceeded for the type T
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct CapError<T>(pub PhantomData<T>);
+pub struct CapError<T> {
+    value: u128,
+    marker: PhantomData<T>,
+}
+
+impl<T> CapError<T> {
+    /// Create an error for a `value` that is not in the range of T
+    #[must_use]
+    pub const fn new(value: u128) -> Self {
+        Self {
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// The value that was rejected
+    #[must_use]
+    pub const fn value(&self) -> u128 {
+        self.value
+    }
+
+    /// Returns [`ErrorKind::OutOfRange`]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        ErrorKind::OutOfRange
+    }
+
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn cast<U>(self) -> CapError<U> {
+        CapError::new(self.value)
+    }
+}
+
+impl<T: CapNum> CapError<T> {
+    /// The exclusive upper bound of the range of T
+    #[must_use]
+    pub const fn limit(&self) -> u128 {
+        T::COUNT as u128
+    }
+}
 
 impl<T, U> core::fmt::Display for CapError<T>
 where
@@ -11,13 +54,15 @@ where
     U: core::fmt::Display,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let value = self.value;
         let rng = T::range();
         let start = rng.start;
         let end = rng.end;
-        f.write_fmt(format_args!("value is not in range {start}..{end}"))
+        f.write_fmt(format_args!("value {value} is not in range {start}..{end}"))
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> std::error::Error for CapError<T>
 where
     Self: core::fmt::Display,
@@ -25,14 +70,235 @@ where
 {
 }
 
-pub trait CapNum {
+/// Error produced when converting between capped types and the value is not in the range of the target type T
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapConversionError<T> {
+    value: u128,
+    marker: PhantomData<T>,
+}
+
+impl<T> CapConversionError<T> {
+    const fn new(value: u128) -> Self {
+        Self {
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// The value that could not be converted
+    #[must_use]
+    pub const fn value(&self) -> u128 {
+        self.value
+    }
+
+    /// Returns [`ErrorKind::OutOfRange`]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        ErrorKind::OutOfRange
+    }
+
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn cast<U>(self) -> CapConversionError<U> {
+        CapConversionError::new(self.value)
+    }
+}
+
+impl<T: CapNum> CapConversionError<T> {
+    /// The exclusive upper bound of the range of T
+    #[must_use]
+    pub const fn limit(&self) -> u128 {
+        T::COUNT as u128
+    }
+}
+
+impl<T, U> core::fmt::Display for CapConversionError<T>
+where
+    T: CapNum<Inner = U>,
+    U: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let value = self.value;
+        let rng = T::range();
+        let start = rng.start;
+        let end = rng.end;
+        f.write_fmt(format_args!("value {value} is not in range {start}..{end}"))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for CapConversionError<T>
+where
+    Self: core::fmt::Display,
+    T: core::fmt::Debug,
+{
+}
+
+/// Error produced when parsing a string into the capped type T
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapParseError<T> {
+    /// The string is not a valid number
+    Invalid(ParseIntError),
+    /// The string is a valid number but it is not in the range of T
+    OutOfRange(CapConversionError<T>),
+    /// The string is a valid number but it is negative so it is not in the range of T
+    Negative,
+    /// The string is a valid number but it is greater than [`u128::MAX`] so it is not in the range of T
+    TooLarge,
+}
+
+impl<T> CapParseError<T> {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn cast<U>(self) -> CapParseError<U> {
+        match self {
+            Self::Invalid(err) => CapParseError::Invalid(err),
+            Self::OutOfRange(err) => CapParseError::OutOfRange(err.cast()),
+            Self::Negative => CapParseError::Negative,
+            Self::TooLarge => CapParseError::TooLarge,
+        }
+    }
+
+    /// The value that was parsed, [`None`] if the string is not a valid number or the number does not fit in a [`u128`]
+    #[must_use]
+    pub const fn value(&self) -> Option<u128> {
+        match self {
+            Self::Invalid(_) | Self::Negative | Self::TooLarge => None,
+            Self::OutOfRange(err) => Some(err.value()),
+        }
+    }
+
+    /// Returns [`ErrorKind::Invalid`] or [`ErrorKind::OutOfRange`]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Invalid(_) => ErrorKind::Invalid,
+            Self::OutOfRange(_) | Self::Negative | Self::TooLarge => ErrorKind::OutOfRange,
+        }
+    }
+
+    /// Parse an unsigned integer, telling apart negative and too large numbers from strings that are not numbers
+    fn parse_u128(src: &str, radix: u32) -> Result<u128, Self> {
+        match src.strip_prefix('-') {
+            // `u128::from_str_radix` rejects a leading `-` so parse the digits after it
+            Some(digits) if !digits.starts_with('+') => match u128::from_str_radix(digits, radix) {
+                Ok(0) => Ok(0),
+                Ok(_) => Err(Self::Negative),
+                Err(err) if *err.kind() == IntErrorKind::PosOverflow => Err(Self::Negative),
+                Err(err) => Err(Self::Invalid(err)),
+            },
+            _ => u128::from_str_radix(src, radix).map_err(|err| match err.kind() {
+                IntErrorKind::PosOverflow => Self::TooLarge,
+                _ => Self::Invalid(err),
+            }),
+        }
+    }
+}
+
+impl<T: CapNum> CapParseError<T> {
+    /// The exclusive upper bound of the range of T
+    #[must_use]
+    pub const fn limit(&self) -> u128 {
+        T::COUNT as u128
+    }
+}
+
+impl<T, U> core::fmt::Display for CapParseError<T>
+where
+    T: CapNum<Inner = U>,
+    U: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Invalid(err) => f.write_fmt(format_args!("invalid number: {err}")),
+            Self::OutOfRange(err) => core::fmt::Display::fmt(err, f),
+            Self::Negative => {
+                let rng = T::range();
+                let start = rng.start;
+                let end = rng.end;
+                f.write_fmt(format_args!(
+                    "negative value is not in range {start}..{end}"
+                ))
+            }
+            Self::TooLarge => {
+                let rng = T::range();
+                let start = rng.start;
+                let end = rng.end;
+                f.write_fmt(format_args!(
+                    "value larger than u128::MAX is not in range {start}..{end}"
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for CapParseError<T>
+where
+    Self: core::fmt::Display,
+    T: core::fmt::Debug,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Invalid(err) => Some(err),
+            Self::OutOfRange(_) | Self::Negative | Self::TooLarge => None,
+        }
+    }
+}
+
+/// A capped unsigned integer that holds a value in the range `0..COUNT`.
+///
+/// This is implemented for every capped integer type so code can be generic over them.
+/// Use [`define_capped_int!`](crate::define_capped_int) to implement it for a newtype.
+///
+/// # Safety
+/// [`CapNum::into_inner`] must return a value in [`CapNum::range`] for every value of the type,
+/// and the range must be `0..COUNT`. Unsafe code in this crate relies on this to index without bounds checks.
+#[allow(unsafe_code)]
+pub unsafe trait CapNum: Copy + Ord {
+    /// The underlying integer type
     type Inner;
 
+    /// The smallest value, always `0`
+    const MIN: Self;
+
+    /// The largest value, always `COUNT - 1`
+    const MAX: Self;
+
+    /// The number of values the capped type can hold
+    const COUNT: usize;
+
+    /// The range of inner values that are valid
     fn range() -> Range<Self::Inner>;
+
+    /// Get the inner value
+    fn into_inner(self) -> Self::Inner;
+
+    /// Create a new value from `value` if it is in range.
+    ///
+    /// # Errors
+    /// Will return `Err` if `value` is not in [`CapNum::range`].
+    fn try_from_inner(value: Self::Inner) -> Result<Self, CapError<Self>>;
+
+    /// Create a new value from `value` by modulo the cap
+    fn new_wrap(value: Self::Inner) -> Self;
+
+    /// Calls the `deserialize_*` method matching the integer the type serializes as.
+    ///
+    /// Formats that are not self describing need this to read back what was written.
+    #[doc(hidden)]
+    #[cfg(feature = "serde")]
+    fn __deserialize_inner<'de, D, V>(deserializer: D, visitor: V) -> Result<V::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        V: serde::de::Visitor<'de>,
+    {
+        deserializer.deserialize_u64(visitor)
+    }
 }
 
 macro_rules! num {
-    ($cap_name:ident, $inner:ident, $str_inner:literal) => {
+    ($cap_name:ident, $iter_name:ident, $inner:ident, $signed:ident, $str_inner:literal, $deserialize:ident) => {
         #[doc = concat!("A [`", $str_inner, "`] capped between 0..`N`")]
         #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
         pub struct $cap_name<const N: $inner>($inner);
@@ -44,10 +310,74 @@ macro_rules! num {
                 Self(value % N)
             }
 
+            #[doc = concat!("Create a new [`Self`] from a [`", $str_inner, "`] by clamping it to `N - 1`.")]
+            ///
+            /// There is no value to clamp to when `N` is `0`, this is checked at compile time.
+            ///
+            /// ```compile_fail
+            #[doc = concat!("let _ = capped::", stringify!($cap_name), "::<0>::new_saturating(5);")]
+            /// ```
+            #[must_use]
+            pub const fn new_saturating(value: $inner) -> Self {
+                let () = AssertNonEmpty::<N>::OK;
+                if value < N {
+                    Self(value)
+                } else {
+                    Self(N - 1)
+                }
+            }
+
+            #[doc = concat!("Create a new [`Self`] from a [`", $str_inner, "`] returning [`None`] if it is not in the range `0..N`.")]
+            #[must_use]
+            pub const fn new_checked(value: $inner) -> Option<Self> {
+                if value < N {
+                    Some(Self(value))
+                } else {
+                    None
+                }
+            }
+
+            /// Proportionally map [`Self`] onto the range of another capped type `T`, rounding to the nearest value.
+            ///
+            /// The first and last values of `0..N` map to the first and last values of `T`.
+            /// For example `CapU8::<101>` as a percentage maps `50` onto `500` of `CapU16::<1000>`.
+            ///
+            /// # Panics
+            /// Panics if `T` can hold no values.
+            #[must_use]
+            #[allow(clippy::cast_lossless)]
+            pub fn rescale<T>(self) -> T
+            where
+                T: super::CapNum,
+                T::Inner: TryFrom<u128>,
+            {
+                let from_max = (N - 1) as u128;
+                let to_max = (T::COUNT as u128)
+                    .checked_sub(1)
+                    .expect("cannot rescale to a type that can hold no values");
+                // Both values are at most u64::MAX so this cannot overflow u128
+                let value = if from_max == 0 {
+                    0
+                } else {
+                    (self.0 as u128 * to_max + from_max / 2) / from_max
+                };
+                T::new_wrap(
+                    T::Inner::try_from(value)
+                        .ok()
+                        .expect("value is less than T::COUNT"),
+                )
+            }
+
             /// Add `rhs` to [`Self`] using modulo `N`.
             #[must_use]
             pub const fn wrapping_add(self, rhs: $inner) -> Self {
-                Self((self.0 + rhs % N) % N)
+                // Avoid overflowing the inner type when `N` is close to its maximum
+                let rhs = rhs % N;
+                if self.0 >= N - rhs {
+                    Self(self.0 - (N - rhs))
+                } else {
+                    Self(self.0 + rhs)
+                }
             }
 
             /// Takes the current value of [`Self`] and increments it by 1 wrapping if it exceeds the limit `N`.
@@ -63,6 +393,156 @@ macro_rules! num {
             pub const fn into_inner(self) -> $inner {
                 self.0
             }
+
+            /// Parse a [`Self`] from a string in the given `radix`.
+            ///
+            #[doc = concat!("See [`", $str_inner, "::from_str_radix`]")]
+            ///
+            /// # Errors
+            /// Will return `Err` if the string is not a number or the number is not in the range `0..N`.
+            /// Negative numbers and numbers greater than [`u128::MAX`] are reported as out of range.
+            ///
+            /// # Panics
+            /// Panics if `radix` is not in the range `2..=36`.
+            pub fn from_str_radix(src: &str, radix: u32) -> Result<Self, super::CapParseError<Self>> {
+                let value = super::CapParseError::parse_u128(src, radix)?;
+                <$inner>::try_from(value)
+                    .ok()
+                    .and_then(|v| Self::try_from(v).ok())
+                    .ok_or(super::CapParseError::OutOfRange(
+                        super::CapConversionError::new(value),
+                    ))
+            }
+
+            /// Get the next value, returning [`None`] instead of wrapping if this is the last value.
+            #[must_use]
+            pub const fn next(self) -> Option<Self> {
+                if self.0 + 1 < N {
+                    Some(Self(self.0 + 1))
+                } else {
+                    None
+                }
+            }
+
+            /// Get the previous value, returning [`None`] instead of wrapping if this is the first value.
+            #[must_use]
+            pub const fn prev(self) -> Option<Self> {
+                if self.0 > 0 {
+                    Some(Self(self.0 - 1))
+                } else {
+                    None
+                }
+            }
+
+            /// The signed serial number distance from `other` to `self`, following [RFC 1982](https://www.rfc-editor.org/rfc/rfc1982) with modulus `N`.
+            ///
+            /// The result is positive if `self` is after `other`, and is [`None`] if they are exactly `N / 2` apart where the order is undefined.
+            #[must_use]
+            #[allow(clippy::cast_possible_wrap)]
+            pub const fn serial_distance(self, other: Self) -> Option<$signed> {
+                let forward = if self.0 >= other.0 {
+                    self.0 - other.0
+                } else {
+                    N - (other.0 - self.0)
+                };
+                let backward = if forward == 0 { 0 } else { N - forward };
+                if forward < backward {
+                    Some(forward as $signed)
+                } else if backward < forward {
+                    Some(-(backward as $signed))
+                } else if forward == 0 {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+
+            /// Returns [`true`] if `self` comes after `other` using serial number arithmetic.
+            ///
+            /// See [`Self::serial_distance`]
+            #[must_use]
+            pub const fn is_after(self, other: Self) -> bool {
+                matches!(self.serial_distance(other), Some(d) if d > 0)
+            }
+
+            /// Returns [`true`] if `self` comes before `other` using serial number arithmetic.
+            ///
+            /// See [`Self::serial_distance`]
+            #[must_use]
+            pub const fn is_before(self, other: Self) -> bool {
+                matches!(self.serial_distance(other), Some(d) if d < 0)
+            }
+
+            /// Compare `self` and `other` using serial number arithmetic.
+            ///
+            /// Returns [`None`] if they are exactly `N / 2` apart.
+            ///
+            /// See [`Self::serial_distance`]
+            #[must_use]
+            pub const fn cmp_serial(self, other: Self) -> Option<core::cmp::Ordering> {
+                match self.serial_distance(other) {
+                    Some(d) if d > 0 => Some(core::cmp::Ordering::Greater),
+                    Some(d) if d < 0 => Some(core::cmp::Ordering::Less),
+                    Some(_) => Some(core::cmp::Ordering::Equal),
+                    None => None,
+                }
+            }
+
+            /// Iterate over every value in the range `0..N` in ascending order.
+            #[must_use]
+            pub const fn all() -> $iter_name<N> {
+                $iter_name { front: 0, back: N }
+            }
+
+            /// Iterate over every value in the range `start..N` in ascending order.
+            #[must_use]
+            pub const fn range_from(start: Self) -> $iter_name<N> {
+                $iter_name {
+                    front: start.0,
+                    back: N,
+                }
+            }
+
+            /// Convert to the same type with a cap `M` that is at least `N`.
+            ///
+            /// This is checked at compile time, using an `M` less than `N` fails to compile.
+            #[must_use]
+            pub const fn widen<const M: $inner>(self) -> $cap_name<M> {
+                let () = AssertWiden::<N, M>::OK;
+                $cap_name(self.0)
+            }
+
+            /// Convert to the same type with a different cap `M`.
+            ///
+            /// # Errors
+            /// Will return `Err` if the value is not in the range `0..M`.
+            pub const fn try_recap<const M: $inner>(
+                self,
+            ) -> Result<$cap_name<M>, super::CapConversionError<$cap_name<M>>> {
+                if self.0 < M {
+                    Ok($cap_name(self.0))
+                } else {
+                    Err(super::CapConversionError::new(self.0 as u128))
+                }
+            }
+        }
+
+        struct AssertNonEmpty<const N: $inner>;
+
+        impl<const N: $inner> AssertNonEmpty<N> {
+            const OK: () = assert!(N > 0, "the cap must not be 0");
+        }
+
+        struct AssertWiden<const N: $inner, const M: $inner>;
+
+        impl<const N: $inner, const M: $inner> AssertWiden<N, M> {
+            const OK: () = assert!(N <= M, "cannot widen to a smaller cap");
+        }
+
+        impl<const N: $inner> From<$cap_name<N>> for $inner {
+            fn from(value: $cap_name<N>) -> Self {
+                value.0
+            }
         }
 
         // TODO: Does this violate PartialEq's requirement for transitive relation?
@@ -84,26 +564,145 @@ macro_rules! num {
             }
         }
 
-        impl<const N: $inner> crate::num::CapNum for $cap_name<N> {
+        #[doc = concat!("An iterator over values of [`", stringify!($cap_name), "`] in ascending order.")]
+        ///
+        #[doc = concat!("Created by [`", stringify!($cap_name), "::all`] and [`", stringify!($cap_name), "::range_from`].")]
+        #[derive(Debug, Clone)]
+        pub struct $iter_name<const N: $inner> {
+            front: $inner,
+            back: $inner,
+        }
+
+        impl<const N: $inner> Iterator for $iter_name<N> {
+            type Item = $cap_name<N>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.front < self.back {
+                    let out = $cap_name(self.front);
+                    self.front += 1;
+                    Some(out)
+                } else {
+                    None
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                // The length may not fit in a `usize`, which is why `ExactSizeIterator` is only implemented for narrow types
+                match usize::try_from(self.back - self.front) {
+                    Ok(len) => (len, Some(len)),
+                    Err(_) => (usize::MAX, None),
+                }
+            }
+        }
+
+        impl<const N: $inner> DoubleEndedIterator for $iter_name<N> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                if self.front < self.back {
+                    self.back -= 1;
+                    Some($cap_name(self.back))
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl<const N: $inner> core::iter::FusedIterator for $iter_name<N> {}
+
+        #[allow(unsafe_code)]
+        // Safety: the inner value is always less than `N` which is `COUNT`
+        unsafe impl<const N: $inner> crate::num::CapNum for $cap_name<N> {
             type Inner = $inner;
 
+            const MIN: Self = Self(0);
+
+            const MAX: Self = Self(N - 1);
+
+            #[allow(clippy::cast_possible_truncation)]
+            const COUNT: usize = {
+                assert!(N as u128 <= usize::MAX as u128, "count does not fit in usize");
+                N as usize
+            };
+
             fn range() -> core::ops::Range<Self::Inner> {
                 0..N
             }
+
+            fn into_inner(self) -> Self::Inner {
+                self.0
+            }
+
+            fn try_from_inner(value: Self::Inner) -> Result<Self, super::CapError<Self>> {
+                Self::try_from(value)
+            }
+
+            fn new_wrap(value: Self::Inner) -> Self {
+                Self::new_wrap(value)
+            }
+
+            #[cfg(feature = "serde")]
+            fn __deserialize_inner<'de, D, V>(
+                deserializer: D,
+                visitor: V,
+            ) -> Result<V::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+                V: serde::de::Visitor<'de>,
+            {
+                deserializer.$deserialize(visitor)
+            }
         }
 
         impl<const N: $inner> TryFrom<$inner> for $cap_name<N> {
             type Error = super::CapError<Self>;
 
+            #[allow(clippy::cast_lossless)]
             fn try_from(value: $inner) -> Result<Self, Self::Error> {
                 if value < N {
                     Ok(Self(value))
                 } else {
-                    Err(super::CapError(core::marker::PhantomData))
+                    Err(super::CapError::new(value as u128))
                 }
             }
         }
 
+        impl<const N: $inner> core::str::FromStr for $cap_name<N> {
+            type Err = super::CapParseError<Self>;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::from_str_radix(s, 10)
+            }
+        }
+
+        impl<const N: $inner> core::fmt::Display for $cap_name<N> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl<const N: $inner> core::fmt::LowerHex for $cap_name<N> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::LowerHex::fmt(&self.0, f)
+            }
+        }
+
+        impl<const N: $inner> core::fmt::UpperHex for $cap_name<N> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::UpperHex::fmt(&self.0, f)
+            }
+        }
+
+        impl<const N: $inner> core::fmt::Octal for $cap_name<N> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Octal::fmt(&self.0, f)
+            }
+        }
+
+        impl<const N: $inner> core::fmt::Binary for $cap_name<N> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Binary::fmt(&self.0, f)
+            }
+        }
+
         impl<const N: $inner> core::ops::Deref for $cap_name<N> {
             type Target = $inner;
 
@@ -114,8 +713,6 @@ macro_rules! num {
 
         #[cfg(test)]
         mod tests {
-            use core::marker::PhantomData;
-
             use super::$cap_name;
 
             #[test]
@@ -123,7 +720,7 @@ macro_rules! num {
                 assert_eq!($cap_name::<5>::try_from(4), Ok($cap_name(4)));
                 assert_eq!(
                     $cap_name::<5>::try_from(5),
-                    Err(crate::num::CapError(PhantomData))
+                    Err(crate::num::CapError::new(5))
                 );
                 let c = $cap_name::<10>::new_wrap(29);
                 assert_eq!(c.into_inner(), 9);
@@ -165,6 +762,122 @@ macro_rules! num {
                     $cap_name::<240>(239).wrapping_add(255),
                     $cap_name::<240>(14)
                 );
+                assert_eq!(
+                    $cap_name::<255>(250).wrapping_add(10),
+                    $cap_name::<255>(5)
+                );
+            }
+
+            #[test]
+            fn iterate() {
+                assert_eq!(<$cap_name<4> as crate::num::CapNum>::COUNT, 4);
+                let all: Vec<_> = $cap_name::<4>::all().collect();
+                assert_eq!(all, [0, 1, 2, 3]);
+                assert_eq!($cap_name::<4>::all().size_hint(), (4, Some(4)));
+                assert_eq!($cap_name::<4>::all().rev().next(), Some($cap_name(3)));
+                assert_eq!($cap_name::<0>::all().next(), None);
+
+                let mut it = $cap_name::<6>::range_from($cap_name(3));
+                assert_eq!(it.size_hint(), (3, Some(3)));
+                assert_eq!(it.next_back(), Some($cap_name(5)));
+                assert_eq!(it.next(), Some($cap_name(3)));
+                assert_eq!(it.next(), Some($cap_name(4)));
+                assert_eq!(it.next(), None);
+                assert_eq!(it.next_back(), None);
+            }
+
+            #[test]
+            fn parse_format() {
+                let c: $cap_name<200> = "123".parse().unwrap();
+                assert_eq!(c, 123);
+                assert_eq!(c.to_string(), "123");
+                assert_eq!(format!("{c:>5}"), "  123");
+                assert_eq!(format!("{c:x} {c:X} {c:#o} {c:b}"), "7b 7B 0o173 1111011");
+                assert_eq!($cap_name::<200>::from_str_radix("7b", 16), Ok(c));
+                assert_eq!($cap_name::<200>::from_str_radix("1111011", 2), Ok(c));
+
+                let err = "abc".parse::<$cap_name<200>>().unwrap_err();
+                assert!(matches!(err, crate::num::CapParseError::Invalid(_)));
+                assert!(err.to_string().starts_with("invalid number"));
+
+                let err = "200".parse::<$cap_name<200>>().unwrap_err();
+                assert_eq!(
+                    err,
+                    crate::num::CapParseError::OutOfRange(crate::num::CapConversionError::new(200))
+                );
+                assert_eq!(err.to_string(), "value 200 is not in range 0..200");
+
+                let err = "70000".parse::<$cap_name<200>>().unwrap_err();
+                assert_eq!(err.to_string(), "value 70000 is not in range 0..200");
+
+                let err = "-5".parse::<$cap_name<200>>().unwrap_err();
+                assert_eq!(err, crate::num::CapParseError::Negative);
+                assert_eq!(err.to_string(), "negative value is not in range 0..200");
+                assert_eq!(err.kind(), crate::ErrorKind::OutOfRange);
+                assert_eq!("-0".parse::<$cap_name<200>>(), Ok($cap_name(0)));
+                assert_eq!(
+                    "-1000000000000000000000000000000000000000".parse::<$cap_name<200>>(),
+                    Err(crate::num::CapParseError::Negative)
+                );
+                let err = "1000000000000000000000000000000000000000"
+                    .parse::<$cap_name<200>>()
+                    .unwrap_err();
+                assert_eq!(err, crate::num::CapParseError::TooLarge);
+                assert_eq!((err.value(), err.kind()), (None, crate::ErrorKind::OutOfRange));
+                assert!(matches!(
+                    "-".parse::<$cap_name<200>>(),
+                    Err(crate::num::CapParseError::Invalid(_))
+                ));
+                assert!(matches!(
+                    "-+5".parse::<$cap_name<200>>(),
+                    Err(crate::num::CapParseError::Invalid(_))
+                ));
+            }
+
+            #[test]
+            fn cap_num() {
+                use crate::num::CapNum;
+
+                assert_eq!(<$cap_name<7> as CapNum>::MIN, 0);
+                assert_eq!(<$cap_name<7> as CapNum>::MAX, 6);
+                assert_eq!(<$cap_name<7> as CapNum>::try_from_inner(3), Ok($cap_name(3)));
+                assert!(<$cap_name<7> as CapNum>::try_from_inner(7).is_err());
+                assert_eq!(CapNum::into_inner($cap_name::<7>(5)), 5);
+                assert_eq!(<$cap_name<7> as CapNum>::new_wrap(9), 2);
+            }
+
+            #[test]
+            fn saturating_checked() {
+                assert_eq!($cap_name::<10>::new_saturating(4), 4);
+                assert_eq!($cap_name::<10>::new_saturating(10), 9);
+                assert_eq!($cap_name::<10>::new_saturating(200), 9);
+                assert_eq!($cap_name::<10>::new_checked(9), Some($cap_name(9)));
+                assert_eq!($cap_name::<10>::new_checked(10), None);
+                assert_eq!($cap_name::<1>::new_saturating(200), 0);
+                assert_eq!($cap_name::<0>::new_checked(0), None);
+            }
+
+            #[test]
+            fn rescale() {
+                let percent = $cap_name::<101>::new_wrap(50);
+                assert_eq!(percent.rescale::<crate::CapU16<1000>>(), 500);
+                assert_eq!($cap_name::<101>(100).rescale::<crate::CapU16<1000>>(), 999);
+                assert_eq!($cap_name::<101>(0).rescale::<crate::CapU8<11>>(), 0);
+                assert_eq!($cap_name::<101>(44).rescale::<crate::CapU8<11>>(), 4);
+                assert_eq!($cap_name::<101>(45).rescale::<crate::CapU8<11>>(), 5);
+                assert_eq!($cap_name::<1>(0).rescale::<crate::CapU8<11>>(), 0);
+                assert_eq!(
+                    $cap_name::<3>(2).rescale::<crate::CapU64<{ u64::MAX }>>(),
+                    u64::MAX - 1
+                );
+            }
+
+            #[test]
+            fn next_prev() {
+                assert_eq!($cap_name::<3>(1).next(), Some($cap_name(2)));
+                assert_eq!($cap_name::<3>(2).next(), None);
+                assert_eq!($cap_name::<3>(1).prev(), Some($cap_name(0)));
+                assert_eq!($cap_name::<3>(0).prev(), None);
             }
 
             #[cfg(feature = "serde")]
@@ -178,6 +891,11 @@ macro_rules! num {
                 assert_eq!(obj, vec![$cap_name(6), $cap_name(9)]);
 
                 let res: serde_json::Result<Vec<$cap_name<10>>> = serde_json::from_str("[10]");
+                assert!(res
+                    .unwrap_err()
+                    .to_string()
+                    .contains("number 10 is not in range 0..10"));
+                let res: serde_json::Result<$cap_name<10>> = serde_json::from_str("-1");
                 assert!(res.is_err());
 
                 assert_eq!(
@@ -189,12 +907,114 @@ macro_rules! num {
 
                 Ok(())
             }
+
+            #[cfg(feature = "serde")]
+            #[test]
+            fn serde_formats() {
+                #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+                struct Config {
+                    value: $cap_name<10>,
+                }
+
+                let config: Config = toml::from_str("value = 3").unwrap();
+                assert_eq!(config.value, 3);
+                assert!(toml::from_str::<Config>("value = 10").is_err());
+                assert!(toml::from_str::<Config>("value = -1").is_err());
+
+                let config: Config = serde_yaml::from_str("value: 7").unwrap();
+                assert_eq!(config.value, 7);
+                assert!(serde_yaml::from_str::<Config>("value: -7").is_err());
+
+                let bytes = rmp_serde::to_vec(&config).unwrap();
+                assert_eq!(rmp_serde::from_slice::<Config>(&bytes).unwrap(), config);
+                let bytes = rmp_serde::to_vec(&(5i64,)).unwrap();
+                assert_eq!(rmp_serde::from_slice::<($cap_name<10>,)>(&bytes).unwrap().0, 5);
+                let bytes = rmp_serde::to_vec(&(-5i64,)).unwrap();
+                assert!(rmp_serde::from_slice::<($cap_name<10>,)>(&bytes).is_err());
+
+                let mut bytes = Vec::new();
+                ciborium::into_writer(&config, &mut bytes).unwrap();
+                assert_eq!(ciborium::from_reader::<Config, _>(bytes.as_slice()).unwrap(), config);
+                let mut bytes = Vec::new();
+                ciborium::into_writer(&-5i64, &mut bytes).unwrap();
+                assert!(ciborium::from_reader::<$cap_name<10>, _>(bytes.as_slice()).is_err());
+                let mut bytes = Vec::new();
+                ciborium::into_writer(&12u64, &mut bytes).unwrap();
+                assert!(ciborium::from_reader::<$cap_name<10>, _>(bytes.as_slice()).is_err());
+            }
+        }
+    };
+}
+
+/// Implements [`serde::Deserialize`] for a capped integer accepting any integer that is in range
+#[cfg(feature = "serde")]
+macro_rules! deserialize {
+    ($cap_name:ident, $inner:ident, $deserialize:ident, $str_inner:literal) => {
+        impl<'de, const N: $inner> serde::Deserialize<'de> for $cap_name<N> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::de::Visitor;
+
+                struct CapVisitor<const N: $inner>;
+
+                impl<const N: $inner> Visitor<'_> for CapVisitor<N> {
+                    type Value = $cap_name<N>;
+
+                    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                        formatter
+                            .write_fmt(format_args!(concat!($str_inner, " in the range 0..{}"), N))
+                    }
+
+                    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        self.visit_i128(i128::from(v))
+                    }
+
+                    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match u128::try_from(v) {
+                            Ok(v) => self.visit_u128(v),
+                            Err(_) => {
+                                Err(E::custom(format_args!("number {v} is not in range 0..{N}")))
+                            }
+                        }
+                    }
+
+                    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        self.visit_u128(u128::from(v))
+                    }
+
+                    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        <$inner>::try_from(v)
+                            .ok()
+                            .and_then($cap_name::new_checked)
+                            .ok_or_else(|| {
+                                E::custom(format_args!("number {v} is not in range 0..{N}"))
+                            })
+                    }
+                }
+                deserializer.$deserialize(CapVisitor)
+            }
         }
     };
 }
 
 pub mod cap_u8 {
-    num!(CapU8, u8, "u8");
+    num!(CapU8, CapU8Iter, u8, i8, "u8", deserialize_u8);
+
+    impl<const N: u8> ExactSizeIterator for CapU8Iter<N> {}
 
     #[cfg(feature = "serde")]
     impl<const N: u8> serde::Serialize for CapU8<N> {
@@ -207,39 +1027,12 @@ pub mod cap_u8 {
     }
 
     #[cfg(feature = "serde")]
-    impl<'de, const N: u8> serde::Deserialize<'de> for CapU8<N> {
-        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where
-            D: serde::Deserializer<'de>,
-        {
-            use serde::de::Visitor;
-
-            struct CapU8Visitor<const N: u8>;
-
-            impl<'de, const N: u8> Visitor<'de> for CapU8Visitor<N> {
-                type Value = CapU8<N>;
-
-                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                    formatter.write_fmt(format_args!("U8 in the range 0..{N}"))
-                }
-
-                fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
-                where
-                    E: serde::de::Error,
-                {
-                    if v < u64::from(N) {
-                        Ok(CapU8(u8::try_from(v).expect("v less than N")))
-                    } else {
-                        Err(E::custom(format!("number {v} is greater than {N}")))
-                    }
-                }
-            }
-            deserializer.deserialize_u8(CapU8Visitor)
-        }
-    }
+    deserialize!(CapU8, u8, deserialize_u8, "u8");
 }
 pub mod cap_u16 {
-    num!(CapU16, u16, "u16");
+    num!(CapU16, CapU16Iter, u16, i16, "u16", deserialize_u16);
+
+    impl<const N: u16> ExactSizeIterator for CapU16Iter<N> {}
 
     #[cfg(feature = "serde")]
     impl<const N: u16> serde::Serialize for CapU16<N> {
@@ -252,39 +1045,13 @@ pub mod cap_u16 {
     }
 
     #[cfg(feature = "serde")]
-    impl<'de, const N: u16> serde::Deserialize<'de> for CapU16<N> {
-        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where
-            D: serde::Deserializer<'de>,
-        {
-            use serde::de::Visitor;
-
-            struct CapU16Visitor<const N: u16>;
-
-            impl<'de, const N: u16> Visitor<'de> for CapU16Visitor<N> {
-                type Value = CapU16<N>;
-
-                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                    formatter.write_fmt(format_args!("U16 in the range 0..{N}"))
-                }
-
-                fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
-                where
-                    E: serde::de::Error,
-                {
-                    if v < u64::from(N) {
-                        Ok(CapU16(u16::try_from(v).expect("v less than N")))
-                    } else {
-                        Err(E::custom(format!("number {v} is greater than {N}")))
-                    }
-                }
-            }
-            deserializer.deserialize_u16(CapU16Visitor)
-        }
-    }
+    deserialize!(CapU16, u16, deserialize_u16, "u16");
 }
 pub mod cap_u32 {
-    num!(CapU32, u32, "u32");
+    num!(CapU32, CapU32Iter, u32, i32, "u32", deserialize_u32);
+
+    // Like `Range<u32>` this assumes `usize` is at least 32 bits
+    impl<const N: u32> ExactSizeIterator for CapU32Iter<N> {}
 
     #[cfg(feature = "serde")]
     impl<const N: u32> serde::Serialize for CapU32<N> {
@@ -297,39 +1064,11 @@ pub mod cap_u32 {
     }
 
     #[cfg(feature = "serde")]
-    impl<'de, const N: u32> serde::Deserialize<'de> for CapU32<N> {
-        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where
-            D: serde::Deserializer<'de>,
-        {
-            use serde::de::Visitor;
-
-            struct CapU32Visitor<const N: u32>;
-
-            impl<'de, const N: u32> Visitor<'de> for CapU32Visitor<N> {
-                type Value = CapU32<N>;
-
-                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                    formatter.write_fmt(format_args!("U32 in the range 0..{N}"))
-                }
-
-                fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
-                where
-                    E: serde::de::Error,
-                {
-                    if v < u64::from(N) {
-                        Ok(CapU32(u32::try_from(v).expect("v less than N")))
-                    } else {
-                        Err(E::custom(format!("number {v} is greater than {N}")))
-                    }
-                }
-            }
-            deserializer.deserialize_u32(CapU32Visitor)
-        }
-    }
+    deserialize!(CapU32, u32, deserialize_u32, "u32");
 }
 pub mod cap_u64 {
-    num!(CapU64, u64, "u64");
+    // `CapU64Iter` is not an `ExactSizeIterator` because its length may not fit in a `usize`, like `Range<u64>`
+    num!(CapU64, CapU64Iter, u64, i64, "u64", deserialize_u64);
 
     #[cfg(feature = "serde")]
     impl<const N: u64> serde::Serialize for CapU64<N> {
@@ -342,39 +1081,19 @@ pub mod cap_u64 {
     }
 
     #[cfg(feature = "serde")]
-    impl<'de, const N: u64> serde::Deserialize<'de> for CapU64<N> {
-        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where
-            D: serde::Deserializer<'de>,
-        {
-            use serde::de::Visitor;
-
-            struct CapU64Visitor<const N: u64>;
-
-            impl<'de, const N: u64> Visitor<'de> for CapU64Visitor<N> {
-                type Value = CapU64<N>;
-
-                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                    formatter.write_fmt(format_args!("u64 in the range 0..{N}"))
-                }
-
-                fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
-                where
-                    E: serde::de::Error,
-                {
-                    if v < N {
-                        Ok(CapU64(v))
-                    } else {
-                        Err(E::custom(format!("number {v} is greater than {N}")))
-                    }
-                }
-            }
-            deserializer.deserialize_u64(CapU64Visitor)
-        }
-    }
+    deserialize!(CapU64, u64, deserialize_u64, "u64");
 }
 pub mod cap_usize {
-    num!(CapUsize, usize, "usize");
+    num!(
+        CapUsize,
+        CapUsizeIter,
+        usize,
+        isize,
+        "usize",
+        deserialize_u64
+    );
+
+    impl<const N: usize> ExactSizeIterator for CapUsizeIter<N> {}
 
     #[cfg(feature = "serde")]
     impl<const N: usize> serde::Serialize for CapUsize<N> {
@@ -387,34 +1106,142 @@ pub mod cap_usize {
     }
 
     #[cfg(feature = "serde")]
-    impl<'de, const N: usize> serde::Deserialize<'de> for CapUsize<N> {
-        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where
-            D: serde::Deserializer<'de>,
-        {
-            use serde::de::Visitor;
+    deserialize!(CapUsize, usize, deserialize_u64, "usize");
+}
+
+mod convert {
+    use super::cap_u16::CapU16;
+    use super::cap_u32::CapU32;
+    use super::cap_u64::CapU64;
+    use super::cap_u8::CapU8;
+    use super::cap_usize::CapUsize;
+    use super::CapConversionError;
 
-            struct CapUsizeVisitor<const N: usize>;
+    /// Implements lossless conversions from a capped type into wider primitives
+    macro_rules! into_primitive {
+        ($cap_name:ident, $inner:ident => $($prim:ident),*) => {
+            $(
+                impl<const N: $inner> From<$cap_name<N>> for $prim {
+                    fn from(value: $cap_name<N>) -> Self {
+                        Self::from(value.into_inner())
+                    }
+                }
+            )*
+        };
+    }
 
-            impl<'de, const N: usize> Visitor<'de> for CapUsizeVisitor<N> {
-                type Value = CapUsize<N>;
+    into_primitive!(CapU8, u8 => u16, u32, u64, u128, usize);
+    into_primitive!(CapU16, u16 => u32, u64, u128, usize);
+    into_primitive!(CapU32, u32 => u64, u128);
+    into_primitive!(CapU64, u64 => u128);
 
-                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                    formatter.write_fmt(format_args!("usize in the range 0..{N}"))
+    /// Implements an infallible compile time checked widening conversion between capped types
+    macro_rules! widen {
+        ($cap_name:ident, $inner:ident => $wide_cap_name:ident, $wide_inner:ident, $method:ident, $assert:ident) => {
+            struct $assert<const N: $inner, const M: $wide_inner>;
+
+            impl<const N: $inner, const M: $wide_inner> $assert<N, M> {
+                const OK: () = assert!(
+                    (N as u128) <= (M as u128),
+                    "cannot widen to a smaller cap"
+                );
+            }
+
+            impl<const N: $inner> $cap_name<N> {
+                #[doc = concat!("Convert to a [`", stringify!($wide_cap_name), "`] with a cap `M` that is at least `N`.")]
+                ///
+                /// This is checked at compile time, using an `M` less than `N` fails to compile.
+                #[must_use]
+                pub fn $method<const M: $wide_inner>(self) -> $wide_cap_name<M> {
+                    let () = $assert::<N, M>::OK;
+                    $wide_cap_name::new_wrap(<$wide_inner>::from(self.into_inner()))
                 }
+            }
+        };
+    }
 
-                fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
-                where
-                    E: serde::de::Error,
+    widen!(CapU8, u8 => CapU16, u16, widen_u16, AssertU8U16);
+    widen!(CapU8, u8 => CapU32, u32, widen_u32, AssertU8U32);
+    widen!(CapU8, u8 => CapU64, u64, widen_u64, AssertU8U64);
+    widen!(CapU8, u8 => CapUsize, usize, widen_usize, AssertU8Usize);
+    widen!(CapU16, u16 => CapU32, u32, widen_u32, AssertU16U32);
+    widen!(CapU16, u16 => CapU64, u64, widen_u64, AssertU16U64);
+    widen!(CapU16, u16 => CapUsize, usize, widen_usize, AssertU16Usize);
+    widen!(CapU32, u32 => CapU64, u64, widen_u64, AssertU32U64);
+
+    /// Implements fallible conversions between capped types of different widths
+    macro_rules! try_convert {
+        ($from_cap_name:ident, $from_inner:ident => $($to_cap_name:ident, $to_inner:ident);*) => {
+            $(
+                impl<const N: $from_inner, const M: $to_inner> TryFrom<$from_cap_name<N>>
+                    for $to_cap_name<M>
                 {
-                    if v < N as u64 {
-                        Ok(CapUsize(usize::try_from(v).expect("v less than N")))
-                    } else {
-                        Err(E::custom(format!("number {v} is greater than {N}")))
+                    type Error = CapConversionError<Self>;
+
+                    #[allow(clippy::cast_lossless)]
+                    fn try_from(value: $from_cap_name<N>) -> Result<Self, Self::Error> {
+                        let value = value.into_inner();
+                        <$to_inner>::try_from(value)
+                            .ok()
+                            .and_then(|v| Self::try_from(v).ok())
+                            .ok_or(CapConversionError::new(value as u128))
                     }
                 }
-            }
-            deserializer.deserialize_u64(CapUsizeVisitor)
+            )*
+        };
+    }
+
+    try_convert!(CapU8, u8 => CapU16, u16; CapU32, u32; CapU64, u64; CapUsize, usize);
+    try_convert!(CapU16, u16 => CapU8, u8; CapU32, u32; CapU64, u64; CapUsize, usize);
+    try_convert!(CapU32, u32 => CapU8, u8; CapU16, u16; CapU64, u64; CapUsize, usize);
+    try_convert!(CapU64, u64 => CapU8, u8; CapU16, u16; CapU32, u32; CapUsize, usize);
+    try_convert!(CapUsize, usize => CapU8, u8; CapU16, u16; CapU32, u32; CapU64, u64);
+
+    #[cfg(test)]
+    mod tests {
+        use super::{CapU16, CapU32, CapU64, CapU8, CapUsize};
+
+        #[test]
+        fn widen() {
+            let c = CapU8::<10>::new_wrap(7);
+            assert_eq!(c.widen::<10>(), CapU8::<10>::new_wrap(7));
+            assert_eq!(c.widen::<200>(), CapU8::<200>::new_wrap(7));
+            assert_eq!(c.widen_u16::<100>(), CapU16::<100>::new_wrap(7));
+            assert_eq!(c.widen_usize::<10>(), CapUsize::<10>::new_wrap(7));
+            assert_eq!(
+                CapU32::<70_000>::new_wrap(69_999).widen_u64::<{ u64::MAX }>(),
+                69_999
+            );
+        }
+
+        #[test]
+        fn into_primitive() {
+            let c = CapU8::<10>::new_wrap(7);
+            assert_eq!(u8::from(c), 7);
+            assert_eq!(u16::from(c), 7);
+            assert_eq!(usize::from(c), 7);
+            assert_eq!(u128::from(CapU64::<{ u64::MAX }>::new_wrap(5)), 5);
+        }
+
+        #[test]
+        fn try_convert() {
+            assert_eq!(
+                CapU8::<4>::try_from(CapU64::<100>::new_wrap(3)),
+                Ok(CapU8::<4>::new_wrap(3))
+            );
+            let err = CapU8::<4>::try_from(CapU64::<100>::new_wrap(50)).unwrap_err();
+            assert_eq!(err.value(), 50);
+            assert_eq!(err.to_string(), "value 50 is not in range 0..4");
+
+            let err = CapU8::<255>::try_from(CapU32::<1000>::new_wrap(300)).unwrap_err();
+            assert_eq!(err.value(), 300);
+
+            assert_eq!(
+                CapUsize::<10>::new_wrap(4).try_recap::<5>(),
+                Ok(CapUsize::<5>::new_wrap(4))
+            );
+            let err = CapUsize::<10>::new_wrap(7).try_recap::<5>().unwrap_err();
+            assert_eq!(err.to_string(), "value 7 is not in range 0..5");
         }
     }
 }
@@ -0,0 +1,144 @@
+use std::borrow::Cow;
+
+use utoipa::openapi::schema::{
+    ArrayBuilder, KnownFormat, ObjectBuilder, Schema, SchemaFormat, Type,
+};
+use utoipa::openapi::RefOr;
+use utoipa::{PartialSchema, ToSchema};
+
+use crate::{CapString, CapU16, CapU32, CapU64, CapU8, CapUsize, CapVec};
+
+/// Implements [`ToSchema`] for a capped integer as an integer in the range `0..=N - 1`
+macro_rules! num_schema {
+    ($cap_name: ident, $inner: ty, $format: ident) => {
+        impl<const N: $inner> PartialSchema for $cap_name<N> {
+            fn schema() -> RefOr<Schema> {
+                let builder = ObjectBuilder::new()
+                    .schema_type(Type::Integer)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::$format)))
+                    .minimum(Some(0));
+                // There is no inclusive maximum when nothing is in range
+                match N.checked_sub(1) {
+                    Some(max) => builder.maximum(Some(max)),
+                    None => builder.exclusive_maximum(Some(0)),
+                }
+                .into()
+            }
+        }
+
+        impl<const N: $inner> ToSchema for $cap_name<N> {
+            fn name() -> Cow<'static, str> {
+                format!(concat!(stringify!($cap_name), "_{}"), N).into()
+            }
+        }
+    };
+}
+
+num_schema!(CapU8, u8, Int32);
+num_schema!(CapU16, u16, Int32);
+num_schema!(CapU32, u32, Int64);
+num_schema!(CapU64, u64, Int64);
+num_schema!(CapUsize, usize, Int64);
+
+impl<const N: usize> PartialSchema for CapString<N> {
+    fn schema() -> RefOr<Schema> {
+        // `maxLength` counts characters which is only exact for ASCII, every character is at least one byte
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .max_length(Some(N))
+            .description(Some(format!("At most {N} bytes when encoded as UTF-8")))
+            .into()
+    }
+}
+
+impl<const N: usize> ToSchema for CapString<N> {
+    fn name() -> Cow<'static, str> {
+        format!("CapString_{N}").into()
+    }
+}
+
+impl<const N: usize, T: PartialSchema> PartialSchema for CapVec<N, T> {
+    fn schema() -> RefOr<Schema> {
+        ArrayBuilder::new()
+            .items(T::schema())
+            .max_items(Some(N))
+            .into()
+    }
+}
+
+// `#[derive(ToSchema)]` only knows how to compose its own generic types,
+// so a `CapVec` field needs `#[schema(schema_with = <CapVec<N, T> as PartialSchema>::schema)]`.
+impl<const N: usize, T: ToSchema> ToSchema for CapVec<N, T> {
+    fn name() -> Cow<'static, str> {
+        format!("CapVec_{N}_{}", T::name()).into()
+    }
+
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        T::schemas(schemas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use utoipa::{PartialSchema, ToSchema};
+
+    use crate::{CapString, CapU32, CapU8, CapVec};
+
+    fn to_json<T: PartialSchema>() -> serde_json::Value {
+        serde_json::to_value(T::schema()).unwrap()
+    }
+
+    #[test]
+    fn schema() {
+        assert_eq!(
+            to_json::<CapString<5>>(),
+            json!({
+                "type": "string",
+                "maxLength": 5,
+                "description": "At most 5 bytes when encoded as UTF-8",
+            })
+        );
+        assert_eq!(
+            to_json::<CapU32<100>>(),
+            json!({ "type": "integer", "format": "int64", "minimum": 0, "maximum": 99 })
+        );
+        assert_eq!(to_json::<CapU8<0>>()["exclusiveMaximum"], 0);
+        assert_eq!(
+            to_json::<CapVec<3, CapU8<10>>>(),
+            json!({
+                "type": "array",
+                "items": { "type": "integer", "format": "int32", "minimum": 0, "maximum": 9 },
+                "maxItems": 3,
+            })
+        );
+        assert_eq!(CapVec::<3, CapString<8>>::name(), "CapVec_3_CapString_8");
+    }
+
+    #[test]
+    fn derive() {
+        #[derive(ToSchema)]
+        #[allow(dead_code)]
+        struct Doc {
+            name: CapString<8>,
+            #[schema(schema_with = <CapVec<3, CapU8<10>> as PartialSchema>::schema)]
+            tags: CapVec<3, CapU8<10>>,
+        }
+
+        let schema = to_json::<Doc>();
+        assert_eq!(
+            schema["properties"]["name"],
+            json!({ "$ref": "#/components/schemas/CapString_8" })
+        );
+        assert_eq!(schema["properties"]["tags"]["maxItems"], 3);
+        assert_eq!(schema["properties"]["tags"]["items"]["maximum"], 9);
+
+        let mut schemas = Vec::new();
+        Doc::schemas(&mut schemas);
+        let schemas: serde_json::Map<_, _> = schemas
+            .into_iter()
+            .map(|(name, schema)| (name, serde_json::to_value(schema).unwrap()))
+            .collect();
+        assert_eq!(schemas["CapString_8"]["maxLength"], 8);
+    }
+}
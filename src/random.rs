@@ -0,0 +1,258 @@
+//! Random generation of capped values, enabled with the `rand` feature.
+//!
+//! Capped integers implement [`Distribution`] for [`StandardUniform`] which samples uniformly from `0..N` without modulo bias,
+//! and [`SampleUniform`] so they can be used with [`Uniform`](rand::distr::Uniform) and [`RngExt::random_range`].
+//! Sampling only uses the given [`Rng`] so the results are reproducible with a seeded generator.
+//!
+//! ```
+//! use capped::CapU16;
+//! use rand::{RngExt as _, SeedableRng as _};
+//!
+//! let mut rng = rand::rngs::Xoshiro256PlusPlus::seed_from_u64(7);
+//! let shard: CapU16<64> = rng.random();
+//! let slot = rng.random_range(CapU16::<64>::new_wrap(10)..CapU16::new_wrap(20));
+//! assert!(*slot >= 10 && *slot < 20);
+//! # let _ = shard;
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+use rand::distr::uniform::{Error, SampleBorrow, SampleUniform, UniformSampler};
+use rand::distr::{Distribution, StandardUniform};
+use rand::{Rng, RngExt as _};
+
+use crate::num::CapNum;
+#[cfg(feature = "alloc")]
+use crate::{CapString, CapStringLengthError, CapVec, CapVecLengthError};
+use crate::{CapU16, CapU32, CapU64, CapU8, CapUsize};
+
+/// The [`UniformSampler`] for capped integers, samples the inner integer between two capped values.
+pub struct UniformCap<T>
+where
+    T: CapNum,
+    T::Inner: SampleUniform,
+{
+    inner: <T::Inner as SampleUniform>::Sampler,
+}
+
+impl<T> Clone for UniformCap<T>
+where
+    T: CapNum,
+    T::Inner: SampleUniform,
+    <T::Inner as SampleUniform>::Sampler: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for UniformCap<T>
+where
+    T: CapNum,
+    T::Inner: SampleUniform,
+    <T::Inner as SampleUniform>::Sampler: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("UniformCap").field(&self.inner).finish()
+    }
+}
+
+impl<T> UniformSampler for UniformCap<T>
+where
+    T: CapNum,
+    T::Inner: SampleUniform,
+{
+    type X = T;
+
+    fn new<B1, B2>(low: B1, high: B2) -> Result<Self, Error>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Ok(Self {
+            inner: UniformSampler::new(low.borrow().into_inner(), high.borrow().into_inner())?,
+        })
+    }
+
+    fn new_inclusive<B1, B2>(low: B1, high: B2) -> Result<Self, Error>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Ok(Self {
+            inner: UniformSampler::new_inclusive(
+                low.borrow().into_inner(),
+                high.borrow().into_inner(),
+            )?,
+        })
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::X {
+        // The sample lies between two capped values so it is always in range
+        T::new_wrap(self.inner.sample(rng))
+    }
+}
+
+macro_rules! random {
+    ($cap_name: ident, $inner: ty) => {
+        /// Samples uniformly from the range `0..N`
+        ///
+        /// # Panics
+        /// Panics if `N` is `0` as there are no values to sample.
+        impl<const N: $inner> Distribution<$cap_name<N>> for StandardUniform {
+            fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> $cap_name<N> {
+                $cap_name::new_wrap(rng.random_range(0..N))
+            }
+        }
+
+        impl<const N: $inner> SampleUniform for $cap_name<N> {
+            type Sampler = UniformCap<Self>;
+        }
+    };
+}
+
+random!(CapU8, u8);
+random!(CapU16, u16);
+random!(CapU32, u32);
+random!(CapU64, u64);
+random!(CapUsize, usize);
+
+/// Create a [`CapString`] of `len` characters sampled from `chars`.
+///
+/// ```
+/// use rand::SeedableRng as _;
+///
+/// let mut rng = rand::rngs::Xoshiro256PlusPlus::seed_from_u64(7);
+/// let name = capped::random::string::<_, _, 16>(&mut rng, rand::distr::Uniform::new_inclusive('a', 'z').unwrap(), 8).unwrap();
+/// assert_eq!(name.as_str().len(), 8);
+/// ```
+///
+/// # Errors
+/// Will return `Err` if the sampled string is longer than `N` bytes,
+/// which is checked before sampling when `len` alone is more than `N`.
+#[cfg(feature = "alloc")]
+pub fn string<R, D, const N: usize>(
+    rng: &mut R,
+    chars: D,
+    len: usize,
+) -> Result<CapString<N>, CapStringLengthError<N>>
+where
+    R: Rng + ?Sized,
+    D: Distribution<char>,
+{
+    // Every character is at least one byte
+    if len > N {
+        return Err(CapStringLengthError(len));
+    }
+    let s: String = (0..len).map(|_| chars.sample(rng)).collect();
+    CapString::try_from(s)
+}
+
+/// Create a [`CapVec`] of `len` elements sampled from `elements`.
+///
+/// # Errors
+/// Will return `Err` if `len` is greater than `N`.
+#[cfg(feature = "alloc")]
+pub fn vec<R, D, T, const N: usize>(
+    rng: &mut R,
+    elements: D,
+    len: usize,
+) -> Result<CapVec<N, T>, CapVecLengthError<N>>
+where
+    R: Rng + ?Sized,
+    D: Distribution<T>,
+{
+    if len > N {
+        return Err(CapVecLengthError::new(len));
+    }
+    let v: Vec<T> = (0..len).map(|_| elements.sample(rng)).collect();
+    CapVec::try_from(v)
+}
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+    use rand::distr::Uniform;
+    use rand::rngs::Xoshiro256PlusPlus;
+    use rand::{RngExt as _, SeedableRng as _};
+
+    use crate::{CapNum as _, CapU16, CapU64, CapU8, CapUsize};
+
+    fn letters() -> Uniform<char> {
+        Uniform::new_inclusive('a', 'z').unwrap()
+    }
+
+    fn rng() -> Xoshiro256PlusPlus {
+        Xoshiro256PlusPlus::seed_from_u64(42)
+    }
+
+    #[test]
+    fn uniform() {
+        let mut rng = rng();
+        let mut seen = [0u32; 5];
+        for _ in 0..5000 {
+            let v: CapU8<5> = rng.random();
+            seen[usize::from(*v)] += 1;
+        }
+        assert!(seen.iter().all(|&count| (900..1100).contains(&count)));
+
+        let big: CapU64<{ u64::MAX }> = rng.random();
+        assert_ne!(big, u64::MAX);
+        let index: CapUsize<1> = rng.random();
+        assert_eq!(index, 0);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = rng();
+        let low = CapU16::<1000>::new_wrap(990);
+        for _ in 0..100 {
+            let v = rng.random_range(low..=CapU16::MAX);
+            assert!(*v >= 990);
+        }
+        let dist = Uniform::new(CapU8::<10>::new_wrap(2), CapU8::new_wrap(4)).unwrap();
+        assert!(rng.sample_iter(dist).take(100).all(|v| v == 2 || v == 3));
+        assert!(Uniform::new(low, low).is_err());
+    }
+
+    #[test]
+    fn seeded() {
+        let a: Vec<CapU16<300>> = rng()
+            .sample_iter(rand::distr::StandardUniform)
+            .take(10)
+            .collect();
+        let b: Vec<CapU16<300>> = rng()
+            .sample_iter(rand::distr::StandardUniform)
+            .take(10)
+            .collect();
+        assert_eq!(a, b);
+        let s1 = super::string::<_, _, 10>(&mut rng(), letters(), 10).unwrap();
+        let s2 = super::string::<_, _, 10>(&mut rng(), letters(), 10).unwrap();
+        assert_eq!(s1, s2);
+    }
+
+    #[test]
+    fn collections() {
+        let mut rng = rng();
+        let s = super::string::<_, _, 4>(&mut rng, letters(), 4).unwrap();
+        assert_eq!(s.as_str().len(), 4);
+        assert!(super::string::<_, _, 4>(&mut rng, letters(), 5).is_err());
+        let v = super::vec::<_, _, CapU8<3>, 8>(&mut rng, rand::distr::StandardUniform, 8).unwrap();
+        assert_eq!(v.len(), 8);
+        assert!(v.as_ref().iter().all(|x| **x < 3));
+        let err = super::vec::<_, _, u8, 8>(&mut rng, rand::distr::StandardUniform, 9).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cap vec length error, vec of length 9 is longer than 8"
+        );
+        // Oversize lengths are rejected before anything is sampled or allocated
+        let err = super::vec::<_, _, u8, 8>(&mut rng, rand::distr::StandardUniform, usize::MAX)
+            .unwrap_err();
+        assert_eq!(err.actual(), usize::MAX);
+        let err = super::string::<_, _, 4>(&mut rng, letters(), usize::MAX).unwrap_err();
+        assert_eq!(err.actual(), usize::MAX);
+    }
+}
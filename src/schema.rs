@@ -0,0 +1,223 @@
+use alloc::borrow::Cow;
+use alloc::format;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::{
+    CapArrayMap, CapBitSet, CapByteArray, CapBytes, CapNum, CapString, CapU16, CapU32, CapU64,
+    CapU8, CapUsize, CapVec,
+};
+
+/// Implements [`JsonSchema`] for a capped integer as an integer in the range `0..N`
+macro_rules! num_schema {
+    ($cap_name: ident, $inner: ty, $format: literal) => {
+        impl<const N: $inner> JsonSchema for $cap_name<N> {
+            fn schema_name() -> Cow<'static, str> {
+                format!(concat!(stringify!($cap_name), "_{}"), N).into()
+            }
+
+            fn schema_id() -> Cow<'static, str> {
+                format!(concat!("capped::", stringify!($cap_name), "<{}>"), N).into()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                json_schema!({
+                    "type": "integer",
+                    "format": $format,
+                    "minimum": 0,
+                    "exclusiveMaximum": N,
+                })
+            }
+        }
+    };
+}
+
+num_schema!(CapU8, u8, "uint8");
+num_schema!(CapU16, u16, "uint16");
+num_schema!(CapU32, u32, "uint32");
+num_schema!(CapU64, u64, "uint64");
+num_schema!(CapUsize, usize, "uint");
+
+impl<const N: usize> JsonSchema for CapString<N> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("CapString_{N}").into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("capped::CapString<{N}>").into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        // `maxLength` counts characters which is only exact for ASCII, every character is at least one byte
+        json_schema!({
+            "type": "string",
+            "maxLength": N,
+            "description": format!("At most {N} bytes when encoded as UTF-8"),
+        })
+    }
+}
+
+impl<const N: usize, T: JsonSchema> JsonSchema for CapVec<N, T> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("CapVec_{N}_of_{}", T::schema_name()).into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("capped::CapVec<{N}, {}>", T::schema_id()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "array",
+            "items": generator.subschema_for::<T>(),
+            "maxItems": N,
+        })
+    }
+}
+
+impl<K, const W: usize> JsonSchema for CapBitSet<K, W>
+where
+    K: CapNum + JsonSchema,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn schema_name() -> Cow<'static, str> {
+        format!("CapBitSet_of_{}", K::schema_name()).into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("capped::CapBitSet<{}>", K::schema_id()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "array",
+            "items": generator.subschema_for::<K>(),
+            "uniqueItems": true,
+            "maxItems": K::COUNT,
+        })
+    }
+}
+
+impl<K, V: JsonSchema, const N: usize> JsonSchema for CapArrayMap<K, V, N>
+where
+    K: CapNum,
+    K::Inner: TryFrom<usize> + TryInto<usize>,
+{
+    fn schema_name() -> Cow<'static, str> {
+        format!("CapArrayMap_{N}_of_{}", V::schema_name()).into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("capped::CapArrayMap<{N}, {}>", V::schema_id()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "additionalProperties": generator.subschema_for::<V>(),
+            "maxProperties": N,
+        })
+    }
+}
+
+/// The longest base64 string for `n` bytes including padding
+const fn base64_len(n: usize) -> usize {
+    (n + 2) / 3 * 4
+}
+
+impl<const N: usize> JsonSchema for CapBytes<N> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("CapBytes_{N}").into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("capped::CapBytes<{N}>").into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "contentEncoding": "base64",
+            "maxLength": base64_len(N),
+            "description": format!("At most {N} bytes encoded as base64"),
+        })
+    }
+}
+
+impl<const N: usize> JsonSchema for CapByteArray<N> {
+    fn schema_name() -> Cow<'static, str> {
+        CapBytes::<N>::schema_name()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        CapBytes::<N>::schema_id()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        CapBytes::<N>::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::{schema_for, JsonSchema};
+    use serde_json::json;
+
+    use crate::{CapArrayMap, CapBitSet, CapBytes, CapString, CapU64, CapU8, CapUsize, CapVec};
+
+    #[test]
+    fn schema() {
+        assert_eq!(
+            schema_for!(CapString<5>).as_value(),
+            &json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "CapString_5",
+                "type": "string",
+                "maxLength": 5,
+                "description": "At most 5 bytes when encoded as UTF-8",
+            })
+        );
+        assert_eq!(
+            schema_for!(CapU64<100>).as_value(),
+            &json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "CapU64_100",
+                "type": "integer",
+                "format": "uint64",
+                "minimum": 0,
+                "exclusiveMaximum": 100,
+            })
+        );
+        assert_eq!(schema_for!(CapBytes<4>).get("maxLength"), Some(&json!(8)));
+        assert_eq!(
+            schema_for!(CapBitSet<CapUsize<3>, 1>).get("items"),
+            Some(&json!({ "$ref": "#/$defs/CapUsize_3" }))
+        );
+    }
+
+    #[test]
+    fn nested_schema() {
+        #[derive(JsonSchema)]
+        #[allow(dead_code)]
+        struct Doc {
+            tags: CapVec<3, CapString<8>>,
+            counts: CapArrayMap<CapU8<24>, u32, 24>,
+        }
+
+        let schema = schema_for!(Doc);
+        let schema = schema.as_value();
+        assert_eq!(
+            schema["properties"]["tags"],
+            json!({ "$ref": "#/$defs/CapVec_3_of_CapString_8" })
+        );
+        assert_eq!(schema["$defs"]["CapVec_3_of_CapString_8"]["maxItems"], 3);
+        assert_eq!(
+            schema["$defs"]["CapVec_3_of_CapString_8"]["items"],
+            json!({ "$ref": "#/$defs/CapString_8" })
+        );
+        assert_eq!(
+            schema["$defs"]["CapArrayMap_24_of_uint32"]["maxProperties"],
+            24
+        );
+    }
+}
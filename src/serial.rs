@@ -0,0 +1,164 @@
+macro_rules! serial {
+    ($serial_name:ident, $cap_name:ident, $inner:ident, $signed:ident) => {
+        #[doc = concat!("A [`", stringify!($cap_name), "`] used as a wrapping sequence number that is ordered using serial number arithmetic.")]
+        ///
+        /// Values are compared following [RFC 1982](https://www.rfc-editor.org/rfc/rfc1982) with modulus `N`,
+        /// so a value just after the wrap point is greater than a value just before it.
+        /// Two values exactly `N / 2` apart are not comparable.
+        ///
+        /// Note that this ordering is not transitive, it is only meaningful for values that are close together.
+        #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+        pub struct $serial_name<const N: $inner>(crate::$cap_name<N>);
+
+        impl<const N: $inner> $serial_name<N> {
+            #[doc = concat!("Create a new [`Self`] from a [`", stringify!($cap_name), "`].")]
+            #[must_use]
+            pub const fn new(value: crate::$cap_name<N>) -> Self {
+                Self(value)
+            }
+
+            #[doc = concat!("Get the inner [`", stringify!($cap_name), "`]")]
+            #[must_use]
+            pub const fn into_inner(self) -> crate::$cap_name<N> {
+                self.0
+            }
+
+            /// Add `rhs` to [`Self`] using modulo `N`.
+            #[must_use]
+            pub const fn wrapping_add(self, rhs: $inner) -> Self {
+                Self(self.0.wrapping_add(rhs))
+            }
+
+            /// Takes the current value of [`Self`] and increments it by 1 wrapping if it exceeds the limit `N`.
+            #[must_use]
+            pub fn take_increment(&mut self) -> Self {
+                Self(self.0.take_increment())
+            }
+
+            #[doc = concat!("See [`", stringify!($cap_name), "::serial_distance`]")]
+            #[must_use]
+            pub const fn distance(self, other: Self) -> Option<$signed> {
+                self.0.serial_distance(other.0)
+            }
+        }
+
+        impl<const N: $inner> From<crate::$cap_name<N>> for $serial_name<N> {
+            fn from(value: crate::$cap_name<N>) -> Self {
+                Self(value)
+            }
+        }
+
+        impl<const N: $inner> From<$serial_name<N>> for crate::$cap_name<N> {
+            fn from(value: $serial_name<N>) -> Self {
+                value.0
+            }
+        }
+
+        impl<const N: $inner> PartialOrd for $serial_name<N> {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                self.0.cmp_serial(other.0)
+            }
+        }
+
+        impl<const N: $inner> core::fmt::Display for $serial_name<N> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<const N: $inner> serde::Serialize for $serial_name<N> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de, const N: $inner> serde::Deserialize<'de> for $serial_name<N> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                <crate::$cap_name<N> as serde::Deserialize>::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+}
+
+serial!(SerialCapU8, CapU8, u8, i8);
+serial!(SerialCapU16, CapU16, u16, i16);
+serial!(SerialCapU32, CapU32, u32, i32);
+serial!(SerialCapU64, CapU64, u64, i64);
+serial!(SerialCapUsize, CapUsize, usize, isize);
+
+#[cfg(test)]
+mod tests {
+    use core::cmp::Ordering;
+
+    use proptest::prelude::*;
+
+    use super::{SerialCapU16, SerialCapU32};
+    use crate::{CapU16, CapU32, CapU8};
+
+    #[test]
+    fn wrap_point() {
+        let a = SerialCapU32::<100>::new(CapU32::new_wrap(98));
+        let b = a.wrapping_add(4);
+        assert_eq!(b.into_inner(), 2);
+        assert!(b > a);
+        assert!(a < b);
+        assert_eq!(b.distance(a), Some(4));
+        assert_eq!(a.distance(b), Some(-4));
+
+        let c = a.wrapping_add(50);
+        assert_eq!(a.partial_cmp(&c), None);
+        assert_eq!(a.distance(c), None);
+        assert_eq!(a.partial_cmp(&a), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn cap_serial_methods() {
+        let a = CapU8::<255>::new_wrap(250);
+        let b = a.wrapping_add(10);
+        assert!(b.is_after(a));
+        assert!(a.is_before(b));
+        assert!(!a.is_after(a));
+        assert_eq!(a.cmp_serial(b), Some(Ordering::Less));
+        // Odd modulus has no undefined pair
+        assert_eq!(a.serial_distance(a.wrapping_add(127)), Some(-127));
+        assert_eq!(a.serial_distance(a.wrapping_add(128)), Some(127));
+    }
+
+    proptest! {
+        #[test]
+        fn ahead_is_after(start in 0u32..1000, step in 1u32..500) {
+            let a = SerialCapU32::<1000>::new(CapU32::new_wrap(start));
+            let b = a.wrapping_add(step);
+            let step = i32::try_from(step).unwrap();
+            prop_assert!(b > a);
+            prop_assert!(a < b);
+            prop_assert_eq!(b.distance(a), Some(step));
+            prop_assert_eq!(a.distance(b), Some(-step));
+        }
+
+        #[test]
+        fn wrap_boundary(before in 1u16..100, after in 0u16..100) {
+            let a = SerialCapU16::<{ u16::MAX }>::new(CapU16::new_wrap(u16::MAX - 1 - before));
+            let b = SerialCapU16::<{ u16::MAX }>::new(CapU16::new_wrap(after));
+            prop_assert!(b > a);
+            prop_assert_eq!(b.distance(a), Some(i16::try_from(before + after + 1).unwrap()));
+        }
+
+        #[test]
+        fn antisymmetric(x in 0u32..64, y in 0u32..64) {
+            let a = CapU32::<64>::new_wrap(x);
+            let b = CapU32::<64>::new_wrap(y);
+            prop_assert_eq!(a.serial_distance(b), b.serial_distance(a).map(|d| -d));
+            prop_assert_eq!(a.cmp_serial(b), b.cmp_serial(a).map(Ordering::reverse));
+            prop_assert_eq!(a.serial_distance(b).is_none(), x.abs_diff(y) == 32);
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use alloc::string::String;
+
 /// A wrapper around [`String`] with a limit on its length, its length must be in the range `0..=N`.
 ///
 /// [`CapString`] is capped in bytes instead of characters.
@@ -107,9 +109,29 @@ impl<const N: usize> CapString<N> {
 }
 
 /// Error returned when converting a string longer than N
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CapStringLengthError<const N: usize>(pub usize);
 
+impl<const N: usize> CapStringLengthError<N> {
+    /// The length that was attempted
+    #[must_use]
+    pub const fn actual(&self) -> usize {
+        self.0
+    }
+
+    /// The cap `N` the length must not exceed
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        N
+    }
+
+    /// Returns [`ErrorKind::TooLong`](crate::ErrorKind::TooLong)
+    #[must_use]
+    pub const fn kind(&self) -> crate::ErrorKind {
+        crate::ErrorKind::TooLong
+    }
+}
+
 impl<const N: usize> core::fmt::Display for CapStringLengthError<N> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let len = self.0;
@@ -119,6 +141,7 @@ impl<const N: usize> core::fmt::Display for CapStringLengthError<N> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<const N: usize> std::error::Error for CapStringLengthError<N> {}
 
 impl<const N: usize> TryFrom<String> for CapString<N> {
@@ -159,7 +182,7 @@ impl<'de, const N: usize> serde::Deserialize<'de> for CapString<N> {
 
         struct CapStringVisitor<const N: usize>;
 
-        impl<'de, const N: usize> Visitor<'de> for CapStringVisitor<N> {
+        impl<const N: usize> Visitor<'_> for CapStringVisitor<N> {
             type Value = CapString<N>;
 
             fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -171,7 +194,7 @@ impl<'de, const N: usize> serde::Deserialize<'de> for CapString<N> {
                 E: serde::de::Error,
             {
                 if v.len() <= N {
-                    Ok(CapString(v.to_owned()))
+                    Ok(CapString(String::from(v)))
                 } else {
                     Err(E::invalid_length(v.len(), &self))
                 }
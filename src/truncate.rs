@@ -0,0 +1,272 @@
+//! Lenient deserialization that truncates oversize input instead of failing, for use with `#[serde(deserialize_with = "...")]`
+//!
+//! Sequences keep their first `N` elements and skip the rest, strings are cut to at most `N` bytes on a [`char`] boundary.
+//! What was dropped is recorded for the current thread while inside [`with_report`].
+//!
+//! ```
+//! use capped::truncate::{self, Dropped};
+//! use capped::{CapString, CapVec};
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Feed {
+//!     #[serde(deserialize_with = "truncate::string::<5, _>")]
+//!     title: CapString<5>,
+//!     #[serde(deserialize_with = "truncate::vec::<2, _, _>")]
+//!     items: CapVec<2, u32>,
+//! }
+//!
+//! let (feed, dropped) = truncate::with_report(|| {
+//!     serde_json::from_str::<Feed>(r#"{"title": "headline", "items": [1, 2, 3]}"#)
+//! });
+//! let feed = feed.unwrap();
+//! assert_eq!(feed.title.as_str(), "headl");
+//! assert_eq!(feed.items.len(), 2);
+//! assert_eq!(dropped, [Dropped::Bytes { kept: 5, dropped: 3 }, Dropped::Elements { kept: 2, dropped: 1 }]);
+//! ```
+
+use core::cell::RefCell;
+use core::marker::PhantomData;
+
+use serde::de::{Deserializer, Error, IgnoredAny, SeqAccess, Visitor};
+use serde::Deserialize;
+
+use crate::{CapString, CapVec};
+
+/// Something that was dropped while truncating
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Dropped {
+    /// Elements skipped from the end of a sequence
+    Elements {
+        /// The number of elements kept
+        kept: usize,
+        /// The number of elements skipped
+        dropped: usize,
+    },
+    /// Bytes cut from the end of a string
+    Bytes {
+        /// The number of bytes kept
+        kept: usize,
+        /// The number of bytes cut
+        dropped: usize,
+    },
+}
+
+thread_local! {
+    static REPORT: RefCell<Option<Vec<Dropped>>> = const { RefCell::new(None) };
+}
+
+fn record(dropped: Dropped) {
+    REPORT.with(|report| {
+        if let Some(report) = report.borrow_mut().as_mut() {
+            report.push(dropped);
+        }
+    });
+}
+
+/// Run `f` returning everything that was truncated on this thread while it ran, in the order it was dropped.
+///
+/// Truncation outside of [`with_report`] is not recorded.
+pub fn with_report<T>(f: impl FnOnce() -> T) -> (T, Vec<Dropped>) {
+    let restore = RestoreReport(REPORT.with(|report| report.borrow_mut().replace(Vec::new())));
+    let value = f();
+    let dropped = REPORT.with(|report| report.borrow_mut().take());
+    drop(restore);
+    (value, dropped.unwrap_or_default())
+}
+
+/// Puts back the report of an outer [`with_report`] when dropped, also when `f` panics
+struct RestoreReport(Option<Vec<Dropped>>);
+
+impl Drop for RestoreReport {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        REPORT.with(|report| *report.borrow_mut() = outer);
+    }
+}
+
+/// Deserialize a [`CapVec`] keeping the first `N` elements and skipping the rest.
+///
+/// # Errors
+/// Will return `Err` if the input is not a sequence or an element fails to deserialize.
+pub fn vec<'de, const N: usize, D, T>(deserializer: D) -> Result<CapVec<N, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct TruncateVecVisitor<const N: usize, T>(PhantomData<T>);
+
+    impl<'de, const N: usize, T: Deserialize<'de>> Visitor<'de> for TruncateVecVisitor<N, T> {
+        type Value = CapVec<N, T>;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("a sequence")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let capacity = seq.size_hint().unwrap_or(0).min(N).min(1024);
+            let mut values = Vec::with_capacity(capacity);
+            while values.len() < N {
+                match seq.next_element()? {
+                    Some(value) => values.push(value),
+                    None => break,
+                }
+            }
+            let mut dropped = 0;
+            while seq.next_element::<IgnoredAny>()?.is_some() {
+                dropped += 1;
+            }
+            if dropped > 0 {
+                record(Dropped::Elements {
+                    kept: values.len(),
+                    dropped,
+                });
+            }
+            CapVec::try_from(values).map_err(A::Error::custom)
+        }
+    }
+
+    deserializer.deserialize_seq(TruncateVecVisitor(PhantomData))
+}
+
+/// Deserialize a [`CapString`] keeping at most `N` bytes, cut on a [`char`] boundary.
+///
+/// # Errors
+/// Will return `Err` if the input is not a string.
+pub fn string<'de, const N: usize, D>(deserializer: D) -> Result<CapString<N>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct TruncateStringVisitor<const N: usize>;
+
+    impl<const N: usize> TruncateStringVisitor<N> {
+        fn truncate<E: Error>(mut v: String) -> Result<CapString<N>, E> {
+            if v.len() > N {
+                let mut end = N;
+                while !v.is_char_boundary(end) {
+                    end -= 1;
+                }
+                record(Dropped::Bytes {
+                    kept: end,
+                    dropped: v.len() - end,
+                });
+                v.truncate(end);
+            }
+            CapString::try_from(v).map_err(E::custom)
+        }
+    }
+
+    impl<const N: usize> Visitor<'_> for TruncateStringVisitor<N> {
+        type Value = CapString<N>;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("a string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            let mut end = v.len().min(N);
+            while !v.is_char_boundary(end) {
+                end -= 1;
+            }
+            // Only copy the part of the string that is kept
+            let mut kept = String::with_capacity(end);
+            kept.push_str(&v[..end]);
+            if end < v.len() {
+                record(Dropped::Bytes {
+                    kept: end,
+                    dropped: v.len() - end,
+                });
+            }
+            CapString::try_from(kept).map_err(E::custom)
+        }
+
+        fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Self::truncate(v)
+        }
+    }
+
+    deserializer.deserialize_string(TruncateStringVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_report, Dropped};
+    use crate::{CapString, CapVec};
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Feed {
+        #[serde(deserialize_with = "super::string::<4, _>")]
+        title: CapString<4>,
+        #[serde(deserialize_with = "super::vec::<2, _, _>")]
+        items: CapVec<2, CapVec<1, u8>>,
+    }
+
+    #[test]
+    fn truncate() {
+        let json = r#"{"title": "abéé", "items": [[1], [2], [3, 4], [[5]]]}"#;
+        let (feed, dropped) = with_report(|| serde_json::from_str::<Feed>(json));
+        let feed = feed.unwrap();
+        // "é" is 2 bytes so only the first one fits
+        assert_eq!(feed.title.as_str(), "ab\u{e9}");
+        assert_eq!(feed.items.len(), 2);
+        assert_eq!(
+            dropped,
+            [
+                Dropped::Bytes {
+                    kept: 4,
+                    dropped: 2
+                },
+                Dropped::Elements {
+                    kept: 2,
+                    dropped: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn no_report() {
+        let (feed, dropped) =
+            with_report(|| serde_json::from_str::<Feed>(r#"{"title": "abc", "items": [[1]]}"#));
+        assert_eq!(feed.unwrap().title.as_str(), "abc");
+        assert!(dropped.is_empty());
+
+        // Outside of `with_report` nothing is recorded
+        let feed: Feed = serde_json::from_str(r#"{"title": "abcdef", "items": []}"#).unwrap();
+        assert_eq!(feed.title.as_str(), "abcd");
+        let ((), dropped) = with_report(|| ());
+        assert!(dropped.is_empty());
+
+        // Kept elements are still checked
+        assert!(serde_json::from_str::<Feed>(r#"{"title": "", "items": [[1, 2]]}"#).is_err());
+    }
+
+    #[test]
+    fn panic_restores_outer() {
+        let json = r#"{"title": "abcdef", "items": []}"#;
+        let ((), dropped) = with_report(|| {
+            let inner = std::panic::catch_unwind(|| with_report(|| panic!("inside the report")));
+            assert!(inner.is_err());
+            serde_json::from_str::<Feed>(json).unwrap();
+        });
+        assert_eq!(
+            dropped,
+            [Dropped::Bytes {
+                kept: 4,
+                dropped: 2
+            }]
+        );
+
+        // Nothing is left recording after the panic
+        std::panic::catch_unwind(|| with_report(|| panic!("outside a report"))).unwrap_err();
+        assert!(super::REPORT.with(|report| report.borrow().is_none()));
+    }
+}
@@ -1,3 +1,7 @@
+use alloc::vec::Vec;
+
+use crate::CapUsize;
+
 /// [`CapVec`] is a [`Vec`] with a limit on its length, its length must be in the range `0..=N`.
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
 pub struct CapVec<const N: usize, T>(Vec<T>);
@@ -39,6 +43,15 @@ impl<const N: usize, T> CapVec<N, T> {
         }
     }
 
+    /// Pushes an element on to the end of the [`CapVec`] if there is space, otherwise returns `element` as the error.
+    ///
+    /// See [`Vec::push`]
+    /// # Errors
+    /// Will return `Err` with `element` if the [`CapVec`] already holds `N` elements.
+    pub fn try_push(&mut self, element: T) -> Result<(), T> {
+        self.push(element).map_or(Ok(()), Err)
+    }
+
     /// Removes the last element from the [`CapVec`] and returns it.
     ///
     /// See [`Vec::pop`]
@@ -65,6 +78,16 @@ impl<const N: usize, T> CapVec<N, T> {
     pub fn into_inner(self) -> Vec<T> {
         self.0
     }
+
+    /// Iterate over the elements paired with their index as a [`CapUsize<N>`].
+    ///
+    /// See [`Iterator::enumerate`]
+    #[must_use]
+    pub fn enumerate_capped(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (CapUsize<N>, &T)> + DoubleEndedIterator {
+        CapUsize::<N>::all().zip(self.0.iter())
+    }
 }
 
 impl<const N: usize, T> AsRef<Vec<T>> for CapVec<N, T> {
@@ -74,9 +97,35 @@ impl<const N: usize, T> AsRef<Vec<T>> for CapVec<N, T> {
 }
 
 /// Error returned when converting a vec longer than N
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CapVecLengthError<const N: usize>(usize);
 
+impl<const N: usize> CapVecLengthError<N> {
+    /// Create an error for a vec of length `len` that is longer than `N`
+    #[must_use]
+    pub const fn new(len: usize) -> Self {
+        Self(len)
+    }
+
+    /// The length that was attempted
+    #[must_use]
+    pub const fn actual(&self) -> usize {
+        self.0
+    }
+
+    /// The cap `N` the length must not exceed
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        N
+    }
+
+    /// Returns [`ErrorKind::TooLong`](crate::ErrorKind::TooLong)
+    #[must_use]
+    pub const fn kind(&self) -> crate::ErrorKind {
+        crate::ErrorKind::TooLong
+    }
+}
+
 impl<const N: usize> core::fmt::Display for CapVecLengthError<N> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let len = self.0;
@@ -86,6 +135,7 @@ impl<const N: usize> core::fmt::Display for CapVecLengthError<N> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<const N: usize> std::error::Error for CapVecLengthError<N> {}
 
 impl<const N: usize, T> From<CapVec<N, T>> for Vec<T> {
@@ -115,7 +165,7 @@ where
     where
         S: serde::Serializer,
     {
-        use serde::ser::SerializeSeq;
+        use serde::ser::SerializeSeq as _;
         let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
         for e in &self.0 {
             seq.serialize_element(e)?;
@@ -132,10 +182,7 @@ impl<'de, const N: usize, T: serde::Deserialize<'de>> serde::Deserialize<'de> fo
     {
         use core::marker::PhantomData;
 
-        use serde::{
-            de::{Error, Visitor},
-            Deserialize,
-        };
+        use serde::{de::Visitor, Deserialize};
 
         struct CapVecVisitor<const N: usize, T>(PhantomData<T>);
 
@@ -146,29 +193,54 @@ impl<'de, const N: usize, T: serde::Deserialize<'de>> serde::Deserialize<'de> fo
                 formatter.write_fmt(format_args!("a sequence of at most {N} elements"))
             }
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
             where
                 A: serde::de::SeqAccess<'de>,
             {
-                let mut values: Vec<T>;
-                if let Some(size) = seq.size_hint() {
-                    values = Vec::with_capacity(size.min(N).min(1024));
-                } else {
-                    values = Vec::with_capacity(N.min(1024));
-                }
-                while let Some(value) = seq.next_element()? {
-                    if values.len() >= N {
-                        return Err(A::Error::invalid_length(values.len(), &self));
-                    }
-                    values.push(value);
-                }
-                Ok(CapVec(values))
+                visit_capped_seq(seq, N, &PhantomData, &self).map(CapVec)
             }
         }
         deserializer.deserialize_seq(CapVecVisitor(PhantomData))
     }
 }
 
+/// Deserializes the elements of `seq` with `seed` failing if there are more than `limit`, used by [`CapVec`] and [`DynCapVec`](crate::DynCapVec).
+#[cfg(feature = "serde")]
+pub fn visit_capped_seq<'de, A, S>(
+    mut seq: A,
+    limit: usize,
+    seed: &S,
+    expected: &dyn serde::de::Expected,
+) -> Result<Vec<S::Value>, A::Error>
+where
+    A: serde::de::SeqAccess<'de>,
+    S: serde::de::DeserializeSeed<'de> + Clone,
+{
+    use serde::de::Error as _;
+
+    let hint = seq.size_hint();
+    if let Some(size) = hint.filter(|size| *size > limit) {
+        return Err(A::Error::invalid_length(size, expected));
+    }
+    // Without a hint grow as elements arrive instead of reserving space that may never be used
+    let mut values = Vec::with_capacity(hint.unwrap_or(0).min(1024));
+    while let Some(value) = seq.next_element_seed(seed.clone())? {
+        if values.len() >= limit {
+            // The rest of the sequence is not read so the real length is unknown
+            let len = values.len() + 1;
+            return Err(A::Error::custom(format_args!(
+                "invalid length of at least {len}, expected {expected}"
+            )));
+        }
+        if values.len() == values.capacity() {
+            // Grow like `Vec` does but never past `limit` so the capacity stays within the cap
+            values.reserve_exact(values.len().max(4).min(limit - values.len()));
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{CapVec, CapVecLengthError};
@@ -199,7 +271,10 @@ mod tests {
         assert_eq!(v.push(1), None);
         assert_eq!(v.push(1), None);
         assert_eq!(v.push(1), Some(1));
+        assert_eq!(v.try_push(2), Err(2));
         assert_eq!(v.pop(), Some(1));
+        assert_eq!(v.try_push(2), Ok(()));
+        assert_eq!(v.pop(), Some(2));
         v.truncate(1);
         assert_eq!(v.len(), 1);
         v.clear();
@@ -208,6 +283,15 @@ mod tests {
         assert_eq!(v.into_inner(), vec![1]);
     }
 
+    #[test]
+    fn enumerate_capped() {
+        let v = CapVec::<4, char>(vec!['a', 'b']);
+        let items: Vec<_> = v.enumerate_capped().map(|(i, c)| (*i, *c)).collect();
+        assert_eq!(items, [(0, 'a'), (1, 'b')]);
+        assert_eq!(v.enumerate_capped().len(), 2);
+        assert_eq!(v.enumerate_capped().next_back().map(|(i, _)| *i), Some(1));
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serde_vec() -> serde_json::Result<()> {
@@ -227,4 +311,41 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_vec_length() {
+        // Reading stops at the first element past the cap so only a lower bound on the length is known
+        let err = serde_json::from_str::<CapVec<3, u32>>("[1, 2, 3, 4, 5]").unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "invalid length of at least 4, expected a sequence of at most 3 elements"
+            ),
+            "{err}"
+        );
+
+        // Formats that know the length up front are rejected before any element is read
+        let bytes = rmp_serde::to_vec(&[1u32; 10]).unwrap();
+        let err = rmp_serde::from_slice::<CapVec<3, u32>>(&bytes).unwrap_err();
+        assert!(err.to_string().contains("invalid length 10"), "{err}");
+
+        // Large caps don't reserve space up front
+        let v: CapVec<100_000, u32> = serde_json::from_str("[]").unwrap();
+        assert_eq!(v.as_ref().capacity(), 0);
+        let v: CapVec<100_000, u32> = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(v.as_ref().capacity(), 10);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_vec_capacity() {
+        // Growing without a size hint must not reserve past the cap
+        let mut v: CapVec<3, u32> = serde_json::from_str("[1, 2]").unwrap();
+        assert!(v.as_ref().capacity() <= 3);
+        assert_eq!(v.push(3), None);
+        assert_eq!(v.push(4), Some(4));
+
+        let v: CapVec<10, u32> = serde_json::from_str("[1, 2, 3, 4, 5, 6, 7, 8, 9]").unwrap();
+        assert!(v.as_ref().capacity() <= 10);
+    }
 }
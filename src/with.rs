@@ -0,0 +1,196 @@
+//! Adapters for `serde_with` that cap plain [`Vec`], [`String`] and map fields without changing their types
+//!
+//! Deserializing runs the same checks as the capped types and fails as soon as the cap is exceeded.
+//! Serializing writes the value unchanged.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use capped::with::{CapMapAs, CapStringAs, CapVecAs};
+//!
+//! #[serde_with::serde_as]
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Event {
+//!     #[serde_as(as = "CapStringAs<64>")]
+//!     name: String,
+//!     #[serde_as(as = "CapVecAs<100>")]
+//!     values: Vec<u32>,
+//!     #[serde_as(as = "CapMapAs<8>")]
+//!     labels: BTreeMap<String, String>,
+//! }
+//!
+//! let event: Event = serde_json::from_str(r#"{"name": "a", "values": [1, 2], "labels": {}}"#).unwrap();
+//! assert_eq!(event.values, [1, 2]);
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::de::DeserializeAsWrap;
+use serde_with::ser::SerializeAsWrap;
+use serde_with::{DeserializeAs, Same, SerializeAs};
+
+/// Caps a [`Vec`] to at most `N` elements, see [`CapVec`](crate::CapVec)
+///
+/// The elements are (de)serialized with `U` like `serde_with`'s `Vec<U>`, for example `CapVecAs<8, CapStringAs<16>>`.
+#[derive(Debug)]
+pub struct CapVecAs<const N: usize, U = Same>(PhantomData<U>);
+
+/// Caps a [`String`] to at most `N` bytes, see [`CapString`](crate::CapString)
+#[derive(Debug)]
+pub struct CapStringAs<const N: usize>;
+
+/// Caps a [`HashMap`] or [`BTreeMap`] to at most `N` entries
+#[derive(Debug)]
+pub struct CapMapAs<const N: usize>;
+
+impl<const N: usize, T, U> SerializeAs<Vec<T>> for CapVecAs<N, U>
+where
+    U: SerializeAs<T>,
+{
+    fn serialize_as<S>(source: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(source.iter().map(SerializeAsWrap::<T, U>::new))
+    }
+}
+
+impl<'de, const N: usize, T, U> DeserializeAs<'de, Vec<T>> for CapVecAs<N, U>
+where
+    U: DeserializeAs<'de, T>,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let values = crate::de::vec::<N, D, DeserializeAsWrap<T, U>>(deserializer)?;
+        Ok(values
+            .into_iter()
+            .map(DeserializeAsWrap::into_inner)
+            .collect())
+    }
+}
+
+impl<const N: usize> SerializeAs<String> for CapStringAs<N> {
+    fn serialize_as<S>(source: &String, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(source)
+    }
+}
+
+impl<'de, const N: usize> DeserializeAs<'de, String> for CapStringAs<N> {
+    fn deserialize_as<D>(deserializer: D) -> Result<String, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        crate::de::string::<N, D>(deserializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize, K, V, H> SerializeAs<HashMap<K, V, H>> for CapMapAs<N>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize_as<S>(source: &HashMap<K, V, H>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(source)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, const N: usize, K, V, H> DeserializeAs<'de, HashMap<K, V, H>> for CapMapAs<N>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    H: BuildHasher + Default,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<HashMap<K, V, H>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        crate::de::map::<N, D, _, K, V>(deserializer)
+    }
+}
+
+impl<const N: usize, K, V> SerializeAs<BTreeMap<K, V>> for CapMapAs<N>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize_as<S>(source: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(source)
+    }
+}
+
+impl<'de, const N: usize, K, V> DeserializeAs<'de, BTreeMap<K, V>> for CapMapAs<N>
+where
+    K: Deserialize<'de> + Ord,
+    V: Deserialize<'de>,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        crate::de::map::<N, D, _, K, V>(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{CapMapAs, CapStringAs, CapVecAs};
+
+    #[serde_with::serde_as]
+    #[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    struct Shared {
+        #[serde_as(as = "CapStringAs<5>")]
+        name: String,
+        #[serde_as(as = "CapVecAs<2>")]
+        tags: Vec<String>,
+        #[serde_as(as = "Option<CapMapAs<1>>")]
+        labels: Option<BTreeMap<String, u8>>,
+        #[serde_as(as = "Vec<CapStringAs<2>>")]
+        codes: Vec<String>,
+        #[serde_as(as = "CapVecAs<2, CapStringAs<3>>")]
+        aliases: Vec<String>,
+    }
+
+    #[test]
+    fn serde_with() -> serde_json::Result<()> {
+        let json = r#"{"name":"hello","tags":["a","b"],"labels":{"x":1},"codes":["ab"],"aliases":["abc"]}"#;
+        let shared: Shared = serde_json::from_str(json)?;
+        assert_eq!(shared.name, "hello");
+        assert_eq!(serde_json::to_string(&shared)?, json);
+
+        let too_long = [
+            r#"{"name":"hello!","tags":[],"labels":null,"codes":[],"aliases":[]}"#,
+            r#"{"name":"","tags":["a","b","c"],"labels":null,"codes":[],"aliases":[]}"#,
+            r#"{"name":"","tags":[],"labels":{"x":1,"y":2},"codes":[],"aliases":[]}"#,
+            r#"{"name":"","tags":[],"labels":null,"codes":["abc"],"aliases":[]}"#,
+            r#"{"name":"","tags":[],"labels":null,"codes":[],"aliases":["a","b","c"]}"#,
+            r#"{"name":"","tags":[],"labels":null,"codes":[],"aliases":["abcd"]}"#,
+        ];
+        for json in too_long {
+            assert!(serde_json::from_str::<Shared>(json).is_err(), "{json}");
+        }
+        Ok(())
+    }
+}